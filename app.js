@@ -8,6 +8,12 @@ const CONFIG = {
   NETWORK_PASSPHRASE: "Test SDF Network ; September 2015",
   SOROBAN_RPC:        "https://soroban-testnet.stellar.org",
   HORIZON_URL:        "https://horizon-testnet.stellar.org",
+  // Stellar Asset Contract of native XLM on Testnet (prize escrow token)
+  XLM_SAC:            "CDLZFC3SYJYDZT7K67VZ75HPJVIEUVNIXF47ZG2FB2RMQQVU2HHGCYSC",
+  // Streamer bond (XLM) returned on reveal, slashed to participants otherwise
+  BOND_XLM:           10,
+  // Participants per register_participants call (contract MAX_BATCH = 200)
+  REGISTER_BATCH:     100,
 };
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -17,6 +23,7 @@ const state = {
   publicKey:          null,
   prizeAmount:        null,     // number (XLM)
   participantsTarget: null,     // number
+  numWinners:         1,        // winners drawn without replacement
   participants:       [],       // Stellar addresses
   secretNumber:       null,     // BigInt
   salt:               null,     // Uint8Array(32)
@@ -293,12 +300,151 @@ function renderParticipants(winnerIdxs) {
 // ─────────────────────────────────────────────────────────────────────────────
 function randomBytes(n) { return crypto.getRandomValues(new Uint8Array(n)); }
 
+// Poseidon over BN254 — mirrors contract/src/poseidon.rs and the Noir stdlib
+// poseidon::bn254::hash_2 (circomlib parameters: t = 3, x^5, 8 full + 57
+// partial rounds). hash_2(a, b) = permutation([0, a, b])[0].
+var FR_MODULUS = 0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001n;
+var POSEIDON_MDS = [
+  ["109b7f411ba0e4c9b2b70caf5c36a7b194be7c11ad24378bfedb68592ba8118b", "16ed41e13bb9c0c66ae119424fddbcbc9314dc9fdbdeea55d6c64543dc4903e0", "2b90bba00fca0589f617e7dcbfe82e0df706ab640ceb247b791a93b74e36736d"],
+  ["2969f27eed31a480b9c36c764379dbca2cc8fdd1415c3dded62940bcde0bd771", "2e2419f9ec02ec394c9871c832963dc1b89d743c8c7b964029b2311687b1fe23", "101071f0032379b697315876690f053d148d4e109f5fb065c8aacc55a0f89bfa"],
+  ["143021ec686a3f330d5f9e654638065ce6cd79e28c5b3753326244ee65a1b1a7", "176cc029695ad02582a70eff08a6fd99d057e12e58e7d7b6b16cdfabc8ee2911", "19a3fc0a56702bf417ba7fee3802593fa644470307043f7773279cd71d25d5e0"]
+].map(function(r) { return r.map(function(h) { return BigInt("0x" + h); }); });
+var POSEIDON_RC = [
+  ["0ee9a592ba9a9518d05986d656f40c2114c4993c11bb29938d21d47304cd8e6e", "00f1445235f2148c5986587169fc1bcd887b08d4d00868df5696fff40956e864", "08dff3487e8ac99e1f29a058d0fa80b930c728730b7ab36ce879f3890ecf73f5"],
+  ["2f27be690fdaee46c3ce28f7532b13c856c35342c84bda6e20966310fadc01d0", "2b2ae1acf68b7b8d2416bebf3d4f6234b763fe04b8043ee48b8327bebca16cf2", "0319d062072bef7ecca5eac06f97d4d55952c175ab6b03eae64b44c7dbf11cfa"],
+  ["28813dcaebaeaa828a376df87af4a63bc8b7bf27ad49c6298ef7b387bf28526d", "2727673b2ccbc903f181bf38e1c1d40d2033865200c352bc150928adddf9cb78", "234ec45ca27727c2e74abd2b2a1494cd6efbd43e340587d6b8fb9e31e65cc632"],
+  ["15b52534031ae18f7f862cb2cf7cf760ab10a8150a337b1ccd99ff6e8797d428", "0dc8fad6d9e4b35f5ed9a3d186b79ce38e0e8a8d1b58b132d701d4eecf68d1f6", "1bcd95ffc211fbca600f705fad3fb567ea4eb378f62e1fec97805518a47e4d9c"],
+  ["10520b0ab721cadfe9eff81b016fc34dc76da36c2578937817cb978d069de559", "1f6d48149b8e7f7d9b257d8ed5fbbaf42932498075fed0ace88a9eb81f5627f6", "1d9655f652309014d29e00ef35a2089bfff8dc1c816f0dc9ca34bdb5460c8705"],
+  ["04df5a56ff95bcafb051f7b1cd43a99ba731ff67e47032058fe3d4185697cc7d", "0672d995f8fff640151b3d290cedaf148690a10a8c8424a7f6ec282b6e4be828", "099952b414884454b21200d7ffafdd5f0c9a9dcc06f2708e9fc1d8209b5c75b9"],
+  ["052cba2255dfd00c7c483143ba8d469448e43586a9b4cd9183fd0e843a6b9fa6", "0b8badee690adb8eb0bd74712b7999af82de55707251ad7716077cb93c464ddc", "119b1590f13307af5a1ee651020c07c749c15d60683a8050b963d0a8e4b2bdd1"],
+  ["03150b7cd6d5d17b2529d36be0f67b832c4acfc884ef4ee5ce15be0bfb4a8d09", "2cc6182c5e14546e3cf1951f173912355374efb83d80898abe69cb317c9ea565", "005032551e6378c450cfe129a404b3764218cadedac14e2b92d2cd73111bf0f9"],
+  ["233237e3289baa34bb147e972ebcb9516469c399fcc069fb88f9da2cc28276b5", "05c8f4f4ebd4a6e3c980d31674bfbe6323037f21b34ae5a4e80c2d4c24d60280", "0a7b1db13042d396ba05d818a319f25252bcf35ef3aeed91ee1f09b2590fc65b"],
+  ["2a73b71f9b210cf5b14296572c9d32dbf156e2b086ff47dc5df542365a404ec0", "1ac9b0417abcc9a1935107e9ffc91dc3ec18f2c4dbe7f22976a760bb5c50c460", "12c0339ae08374823fabb076707ef479269f3e4d6cb104349015ee046dc93fc0"],
+  ["0b7475b102a165ad7f5b18db4e1e704f52900aa3253baac68246682e56e9a28e", "037c2849e191ca3edb1c5e49f6e8b8917c843e379366f2ea32ab3aa88d7f8448", "05a6811f8556f014e92674661e217e9bd5206c5c93a07dc145fdb176a716346f"],
+  ["29a795e7d98028946e947b75d54e9f044076e87a7b2883b47b675ef5f38bd66e", "20439a0c84b322eb45a3857afc18f5826e8c7382c8a1585c507be199981fd22f", "2e0ba8d94d9ecf4a94ec2050c7371ff1bb50f27799a84b6d4a2a6f2a0982c887"],
+  ["143fd115ce08fb27ca38eb7cce822b4517822cd2109048d2e6d0ddcca17d71c8", "0c64cbecb1c734b857968dbbdcf813cdf8611659323dbcbfc84323623be9caf1", "028a305847c683f646fca925c163ff5ae74f348d62c2b670f1426cef9403da53"],
+  ["2e4ef510ff0b6fda5fa940ab4c4380f26a6bcb64d89427b824d6755b5db9e30c", "0081c95bc43384e663d79270c956ce3b8925b4f6d033b078b96384f50579400e", "2ed5f0c91cbd9749187e2fade687e05ee2491b349c039a0bba8a9f4023a0bb38"],
+  ["30509991f88da3504bbf374ed5aae2f03448a22c76234c8c990f01f33a735206", "1c3f20fd55409a53221b7c4d49a356b9f0a1119fb2067b41a7529094424ec6ad", "10b4e7f3ab5df003049514459b6e18eec46bb2213e8e131e170887b47ddcb96c"],
+  ["2a1982979c3ff7f43ddd543d891c2abddd80f804c077d775039aa3502e43adef", "1c74ee64f15e1db6feddbead56d6d55dba431ebc396c9af95cad0f1315bd5c91", "07533ec850ba7f98eab9303cace01b4b9e4f2e8b82708cfa9c2fe45a0ae146a0"],
+  ["21576b438e500449a151e4eeaf17b154285c68f42d42c1808a11abf3764c0750", "2f17c0559b8fe79608ad5ca193d62f10bce8384c815f0906743d6930836d4a9e", "2d477e3862d07708a79e8aae946170bc9775a4201318474ae665b0b1b7e2730e"],
+  ["162f5243967064c390e095577984f291afba2266c38f5abcd89be0f5b2747eab", "2b4cb233ede9ba48264ecd2c8ae50d1ad7a8596a87f29f8a7777a70092393311", "2c8fbcb2dd8573dc1dbaf8f4622854776db2eece6d85c4cf4254e7c35e03b07a"],
+  ["1d6f347725e4816af2ff453f0cd56b199e1b61e9f601e9ade5e88db870949da9", "204b0c397f4ebe71ebc2d8b3df5b913df9e6ac02b68d31324cd49af5c4565529", "0c4cb9dc3c4fd8174f1149b3c63c3c2f9ecb827cd7dc25534ff8fb75bc79c502"],
+  ["174ad61a1448c899a25416474f4930301e5c49475279e0639a616ddc45bc7b54", "1a96177bcf4d8d89f759df4ec2f3cde2eaaa28c177cc0fa13a9816d49a38d2ef", "066d04b24331d71cd0ef8054bc60c4ff05202c126a233c1a8242ace360b8a30a"],
+  ["2a4c4fc6ec0b0cf52195782871c6dd3b381cc65f72e02ad527037a62aa1bd804", "13ab2d136ccf37d447e9f2e14a7cedc95e727f8446f6d9d7e55afc01219fd649", "1121552fca26061619d24d843dc82769c1b04fcec26f55194c2e3e869acc6a9a"],
+  ["00ef653322b13d6c889bc81715c37d77a6cd267d595c4a8909a5546c7c97cff1", "0e25483e45a665208b261d8ba74051e6400c776d652595d9845aca35d8a397d3", "29f536dcb9dd7682245264659e15d88e395ac3d4dde92d8c46448db979eeba89"],
+  ["2a56ef9f2c53febadfda33575dbdbd885a124e2780bbea170e456baace0fa5be", "1c8361c78eb5cf5decfb7a2d17b5c409f2ae2999a46762e8ee416240a8cb9af1", "151aff5f38b20a0fc0473089aaf0206b83e8e68a764507bfd3d0ab4be74319c5"],
+  ["04c6187e41ed881dc1b239c88f7f9d43a9f52fc8c8b6cdd1e76e47615b51f100", "13b37bd80f4d27fb10d84331f6fb6d534b81c61ed15776449e801b7ddc9c2967", "01a5c536273c2d9df578bfbd32c17b7a2ce3664c2a52032c9321ceb1c4e8a8e4"],
+  ["2ab3561834ca73835ad05f5d7acb950b4a9a2c666b9726da832239065b7c3b02", "1d4d8ec291e720db200fe6d686c0d613acaf6af4e95d3bf69f7ed516a597b646", "041294d2cc484d228f5784fe7919fd2bb925351240a04b711514c9c80b65af1d"],
+  ["154ac98e01708c611c4fa715991f004898f57939d126e392042971dd90e81fc6", "0b339d8acca7d4f83eedd84093aef51050b3684c88f8b0b04524563bc6ea4da4", "0955e49e6610c94254a4f84cfbab344598f0e71eaff4a7dd81ed95b50839c82e"],
+  ["06746a6156eba54426b9e22206f15abca9a6f41e6f535c6f3525401ea0654626", "0f18f5a0ecd1423c496f3820c549c27838e5790e2bd0a196ac917c7ff32077fb", "04f6eeca1751f7308ac59eff5beb261e4bb563583ede7bc92a738223d6f76e13"],
+  ["2b56973364c4c4f5c1a3ec4da3cdce038811eb116fb3e45bc1768d26fc0b3758", "123769dd49d5b054dcd76b89804b1bcb8e1392b385716a5d83feb65d437f29ef", "2147b424fc48c80a88ee52b91169aacea989f6446471150994257b2fb01c63e9"],
+  ["0fdc1f58548b85701a6c5505ea332a29647e6f34ad4243c2ea54ad897cebe54d", "12373a8251fea004df68abcf0f7786d4bceff28c5dbbe0c3944f685cc0a0b1f2", "21e4f4ea5f35f85bad7ea52ff742c9e8a642756b6af44203dd8a1f35c1a90035"],
+  ["16243916d69d2ca3dfb4722224d4c462b57366492f45e90d8a81934f1bc3b147", "1efbe46dd7a578b4f66f9adbc88b4378abc21566e1a0453ca13a4159cac04ac2", "07ea5e8537cf5dd08886020e23a7f387d468d5525be66f853b672cc96a88969a"],
+  ["05a8c4f9968b8aa3b7b478a30f9a5b63650f19a75e7ce11ca9fe16c0b76c00bc", "20f057712cc21654fbfe59bd345e8dac3f7818c701b9c7882d9d57b72a32e83f", "04a12ededa9dfd689672f8c67fee31636dcd8e88d01d49019bd90b33eb33db69"],
+  ["27e88d8c15f37dcee44f1e5425a51decbd136ce5091a6767e49ec9544ccd101a", "2feed17b84285ed9b8a5c8c5e95a41f66e096619a7703223176c41ee433de4d1", "1ed7cc76edf45c7c404241420f729cf394e5942911312a0d6972b8bd53aff2b8"],
+  ["15742e99b9bfa323157ff8c586f5660eac6783476144cdcadf2874be45466b1a", "1aac285387f65e82c895fc6887ddf40577107454c6ec0317284f033f27d0c785", "25851c3c845d4790f9ddadbdb6057357832e2e7a49775f71ec75a96554d67c77"],
+  ["15a5821565cc2ec2ce78457db197edf353b7ebba2c5523370ddccc3d9f146a67", "2411d57a4813b9980efa7e31a1db5966dcf64f36044277502f15485f28c71727", "002e6f8d6520cd4713e335b8c0b6d2e647e9a98e12f4cd2558828b5ef6cb4c9b"],
+  ["2ff7bc8f4380cde997da00b616b0fcd1af8f0e91e2fe1ed7398834609e0315d2", "00b9831b948525595ee02724471bcd182e9521f6b7bb68f1e93be4febb0d3cbe", "0a2f53768b8ebf6a86913b0e57c04e011ca408648a4743a87d77adbf0c9c3512"],
+  ["00248156142fd0373a479f91ff239e960f599ff7e94be69b7f2a290305e1198d", "171d5620b87bfb1328cf8c02ab3f0c9a397196aa6a542c2350eb512a2b2bcda9", "170a4f55536f7dc970087c7c10d6fad760c952172dd54dd99d1045e4ec34a808"],
+  ["29aba33f799fe66c2ef3134aea04336ecc37e38c1cd211ba482eca17e2dbfae1", "1e9bc179a4fdd758fdd1bb1945088d47e70d114a03f6a0e8b5ba650369e64973", "1dd269799b660fad58f7f4892dfb0b5afeaad869a9c4b44f9c9e1c43bdaf8f09"],
+  ["22cdbc8b70117ad1401181d02e15459e7ccd426fe869c7c95d1dd2cb0f24af38", "0ef042e454771c533a9f57a55c503fcefd3150f52ed94a7cd5ba93b9c7dacefd", "11609e06ad6c8fe2f287f3036037e8851318e8b08a0359a03b304ffca62e8284"],
+  ["1166d9e554616dba9e753eea427c17b7fecd58c076dfe42708b08f5b783aa9af", "2de52989431a859593413026354413db177fbf4cd2ac0b56f855a888357ee466", "3006eb4ffc7a85819a6da492f3a8ac1df51aee5b17b8e89d74bf01cf5f71e9ad"],
+  ["2af41fbb61ba8a80fdcf6fff9e3f6f422993fe8f0a4639f962344c8225145086", "119e684de476155fe5a6b41a8ebc85db8718ab27889e85e781b214bace4827c3", "1835b786e2e8925e188bea59ae363537b51248c23828f047cff784b97b3fd800"],
+  ["28201a34c594dfa34d794996c6433a20d152bac2a7905c926c40e285ab32eeb6", "083efd7a27d1751094e80fefaf78b000864c82eb571187724a761f88c22cc4e7", "0b6f88a3577199526158e61ceea27be811c16df7774dd8519e079564f61fd13b"],
+  ["0ec868e6d15e51d9644f66e1d6471a94589511ca00d29e1014390e6ee4254f5b", "2af33e3f866771271ac0c9b3ed2e1142ecd3e74b939cd40d00d937ab84c98591", "0b520211f904b5e7d09b5d961c6ace7734568c547dd6858b364ce5e47951f178"],
+  ["0b2d722d0919a1aad8db58f10062a92ea0c56ac4270e822cca228620188a1d40", "1f790d4d7f8cf094d980ceb37c2453e957b54a9991ca38bbe0061d1ed6e562d4", "0171eb95dfbf7d1eaea97cd385f780150885c16235a2a6a8da92ceb01e504233"],
+  ["0c2d0e3b5fd57549329bf6885da66b9b790b40defd2c8650762305381b168873", "1162fb28689c27154e5a8228b4e72b377cbcafa589e283c35d3803054407a18d", "2f1459b65dee441b64ad386a91e8310f282c5a92a89e19921623ef8249711bc0"],
+  ["1e6ff3216b688c3d996d74367d5cd4c1bc489d46754eb712c243f70d1b53cfbb", "01ca8be73832b8d0681487d27d157802d741a6f36cdc2a0576881f9326478875", "1f7735706ffe9fc586f976d5bdf223dc680286080b10cea00b9b5de315f9650e"],
+  ["2522b60f4ea3307640a0c2dce041fba921ac10a3d5f096ef4745ca838285f019", "23f0bee001b1029d5255075ddc957f833418cad4f52b6c3f8ce16c235572575b", "2bc1ae8b8ddbb81fcaac2d44555ed5685d142633e9df905f66d9401093082d59"],
+  ["0f9406b8296564a37304507b8dba3ed162371273a07b1fc98011fcd6ad72205f", "2360a8eb0cc7defa67b72998de90714e17e75b174a52ee4acb126c8cd995f0a8", "15871a5cddead976804c803cbaef255eb4815a5e96df8b006dcbbc2767f88948"],
+  ["193a56766998ee9e0a8652dd2f3b1da0362f4f54f72379544f957ccdeefb420f", "2a394a43934f86982f9be56ff4fab1703b2e63c8ad334834e4309805e777ae0f", "1859954cfeb8695f3e8b635dcb345192892cd11223443ba7b4166e8876c0d142"],
+  ["04e1181763050e58013444dbcb99f1902b11bc25d90bbdca408d3819f4fed32b", "0fdb253dee83869d40c335ea64de8c5bb10eb82db08b5e8b1f5e5552bfd05f23", "058cbe8a9a5027bdaa4efb623adead6275f08686f1c08984a9d7c5bae9b4f1c0"],
+  ["1382edce9971e186497eadb1aeb1f52b23b4b83bef023ab0d15228b4cceca59a", "03464990f045c6ee0819ca51fd11b0be7f61b8eb99f14b77e1e6634601d9e8b5", "23f7bfc8720dc296fff33b41f98ff83c6fcab4605db2eb5aaa5bc137aeb70a58"],
+  ["0a59a158e3eec2117e6e94e7f0e9decf18c3ffd5e1531a9219636158bbaf62f2", "06ec54c80381c052b58bf23b312ffd3ce2c4eba065420af8f4c23ed0075fd07b", "118872dc832e0eb5476b56648e867ec8b09340f7a7bcb1b4962f0ff9ed1f9d01"],
+  ["13d69fa127d834165ad5c7cba7ad59ed52e0b0f0e42d7fea95e1906b520921b1", "169a177f63ea681270b1c6877a73d21bde143942fb71dc55fd8a49f19f10c77b", "04ef51591c6ead97ef42f287adce40d93abeb032b922f66ffb7e9a5a7450544d"],
+  ["256e175a1dc079390ecd7ca703fb2e3b19ec61805d4f03ced5f45ee6dd0f69ec", "30102d28636abd5fe5f2af412ff6004f75cc360d3205dd2da002813d3e2ceeb2", "10998e42dfcd3bbf1c0714bc73eb1bf40443a3fa99bef4a31fd31be182fcc792"],
+  ["193edd8e9fcf3d7625fa7d24b598a1d89f3362eaf4d582efecad76f879e36860", "18168afd34f2d915d0368ce80b7b3347d1c7a561ce611425f2664d7aa51f0b5d", "29383c01ebd3b6ab0c017656ebe658b6a328ec77bc33626e29e2e95b33ea6111"],
+  ["10646d2f2603de39a1f4ae5e7771a64a702db6e86fb76ab600bf573f9010c711", "0beb5e07d1b27145f575f1395a55bf132f90c25b40da7b3864d0242dcb1117fb", "16d685252078c133dc0d3ecad62b5c8830f95bb2e54b59abdffbf018d96fa336"],
+  ["0a6abd1d833938f33c74154e0404b4b40a555bbbec21ddfafd672dd62047f01a", "1a679f5d36eb7b5c8ea12a4c2dedc8feb12dffeec450317270a6f19b34cf1860", "0980fb233bd456c23974d50e0ebfde4726a423eada4e8f6ffbc7592e3f1b93d6"],
+  ["161b42232e61b84cbf1810af93a38fc0cece3d5628c9282003ebacb5c312c72b", "0ada10a90c7f0520950f7d47a60d5e6a493f09787f1564e5d09203db47de1a0b", "1a730d372310ba82320345a29ac4238ed3f07a8a2b4e121bb50ddb9af407f451"],
+  ["2c8120f268ef054f817064c369dda7ea908377feaba5c4dffbda10ef58e8c556", "1c7c8824f758753fa57c00789c684217b930e95313bcb73e6e7b8649a4968f70", "2cd9ed31f5f8691c8e39e4077a74faa0f400ad8b491eb3f7b47b27fa3fd1cf77"],
+  ["23ff4f9d46813457cf60d92f57618399a5e022ac321ca550854ae23918a22eea", "09945a5d147a4f66ceece6405dddd9d0af5a2c5103529407dff1ea58f180426d", "188d9c528025d4c2b67660c6b771b90f7c7da6eaa29d3f268a6dd223ec6fc630"],
+  ["3050e37996596b7f81f68311431d8734dba7d926d3633595e0c0d8ddf4f0f47f", "15af1169396830a91600ca8102c35c426ceae5461e3f95d89d829518d30afd78", "1da6d09885432ea9a06d9f37f873d985dae933e351466b2904284da3320d8acc"],
+  ["2796ea90d269af29f5f8acf33921124e4e4fad3dbe658945e546ee411ddaa9cb", "202d7dd1da0f6b4b0325c8b3307742f01e15612ec8e9304a7cb0319e01d32d60", "096d6790d05bb759156a952ba263d672a2d7f9c788f4c831a29dace4c0f8be5f"],
+  ["054efa1f65b0fce283808965275d877b438da23ce5b13e1963798cb1447d25a4", "1b162f83d917e93edb3308c29802deb9d8aa690113b2e14864ccf6e18e4165f1", "21e5241e12564dd6fd9f1cdd2a0de39eedfefc1466cc568ec5ceb745a0506edc"],
+  ["1cfb5662e8cf5ac9226a80ee17b36abecb73ab5f87e161927b4349e10e4bdf08", "0f21177e302a771bbae6d8d1ecb373b62c99af346220ac0129c53f666eb24100", "1671522374606992affb0dd7f71b12bec4236aede6290546bcef7e1f515c2320"],
+  ["0fa3ec5b9488259c2eb4cf24501bfad9be2ec9e42c5cc8ccd419d2a692cad870", "193c0e04e0bd298357cb266c1506080ed36edce85c648cc085e8c57b1ab54bba", "102adf8ef74735a27e9128306dcbc3c99f6f7291cd406578ce14ea2adaba68f8"],
+  ["0fe0af7858e49859e2a54d6f1ad945b1316aa24bfbdd23ae40a6d0cb70c3eab1", "216f6717bbc7dedb08536a2220843f4e2da5f1daa9ebdefde8a5ea7344798d22", "1da55cc900f0d21f4a3e694391918a1b3c23b2ac773c6b3ef88e2e4228325161"]
+].map(function(r) { return r.map(function(h) { return BigInt("0x" + h); }); });
+
+function poseidonHash2(a, b) {
+  var p = FR_MODULUS, s = [0n, a % p, b % p];
+  var pow5 = function(x) { var x2 = x * x % p; return x2 * x2 % p * x % p; };
+  for (var r = 0; r < 65; r++) {
+    s = s.map(function(x, i) { return (x + POSEIDON_RC[r][i]) % p; });
+    if (r < 4 || r >= 61) s = s.map(pow5); else s[0] = pow5(s[0]);
+    s = POSEIDON_MDS.map(function(row) {
+      return (row[0] * s[0] + row[1] * s[1] + row[2] * s[2]) % p;
+    });
+  }
+  return s[0];
+}
+
+function bytesToBigInt(b) {
+  return BigInt("0x" + (Array.from(b).map(function(x) { return x.toString(16).padStart(2, "0"); }).join("") || "0"));
+}
+function bigIntToBytes32(v) {
+  var hex = v.toString(16).padStart(64, "0"), out = new Uint8Array(32);
+  for (var i = 0; i < 32; i++) out[i] = parseInt(hex.substr(i * 2, 2), 16);
+  return out;
+}
+
+// Random 32-byte salt below the BN254 scalar modulus (top 3 bits cleared)
+function randomFieldSalt() {
+  var salt = randomBytes(32);
+  salt[0] &= 0x1f;
+  return salt;
+}
+
+// commitment = Poseidon(secret_number, salt), 32 bytes big-endian — the
+// value the Noir circuit proves against (CommitmentScheme::Poseidon)
 async function computeCommitment(secretNumber, salt) {
-  var pre  = new Uint8Array(40);
+  return bigIntToBytes32(poseidonHash2(BigInt(secretNumber), bytesToBigInt(salt)));
+}
+
+// Unbiased winner derivation — mirrors contract/src/selection.rs
+//   word(j) = first 8 bytes (BE) of
+//             SHA-256(secret_be8 || salt32 || closeEntropy32 || j_be4)
+//   accept word(j) if word(j) < 2^64 - (2^64 mod range), index = word % range
+//   winners are drawn without replacement; j is shared across winners
+//   closeEntropy is fixed on-chain by close_registrations (get_session)
+async function drawWord(secretNumber, salt, closeEntropy, counter) {
+  var pre  = new Uint8Array(76);
   var view = new DataView(pre.buffer);
   view.setBigUint64(0, BigInt(secretNumber), false);
   pre.set(salt, 8);
-  return new Uint8Array(await crypto.subtle.digest("SHA-256", pre));
+  pre.set(closeEntropy, 40);
+  view.setUint32(72, counter, false);
+  var digest = new DataView(await crypto.subtle.digest("SHA-256", pre));
+  return digest.getBigUint64(0, false);
+}
+
+async function deriveWinners(secretNumber, salt, closeEntropy, n, numWinners) {
+  var TWO64 = 1n << 64n;
+  var winners = [], taken = [], counter = 0;
+  for (var k = 0; k < numWinners; k++) {
+    var range = BigInt(n - k);
+    var limit = TWO64 - (TWO64 % range);
+    var word;
+    do { word = await drawWord(secretNumber, salt, closeEntropy, counter++); } while (word >= limit);
+    var idx = Number(word % range);
+    // skip already-taken indices (ascending walk)
+    var pos = 0;
+    while (pos < taken.length && taken[pos] <= idx) { idx++; pos++; }
+    taken.splice(pos, 0, idx);
+    winners.push(idx);
+  }
+  return winners;
 }
 
 function shortHex(bytes) {
@@ -314,7 +460,6 @@ function getSdk()  {
   return window.StellarSdk;
 }
 function getRpc()  { return new (getSdk()).SorobanRpc.Server(CONFIG.SOROBAN_RPC, { allowHttp: false }); }
-function getHorizon() { return new (getSdk()).Horizon.Server(CONFIG.HORIZON_URL); }
 
 function scAddr(a)  { return new (getSdk()).Address(a).toScVal(); }
 function scU32(n)   { return getSdk().xdr.ScVal.scvU32(n >>> 0); }
@@ -323,6 +468,85 @@ function scU64(n)   {
   return Sdk.xdr.ScVal.scvU64(new Sdk.xdr.Uint64(Number(b & 0xFFFFFFFFn) >>> 0, Number(b >> 32n) >>> 0));
 }
 function scBytes(b) { return getSdk().xdr.ScVal.scvBytes(Buffer.from(b)); }
+function scBool(b)  { return getSdk().xdr.ScVal.scvBool(!!b); }
+function scVoid()   { return getSdk().xdr.ScVal.scvVoid(); }
+function scVec(xs)  { return getSdk().xdr.ScVal.scvVec(xs); }
+// Unit variant of a #[contracttype] enum, e.g. CommitmentScheme::Poseidon
+function scEnum(v)  { return getSdk().xdr.ScVal.scvVec([getSdk().xdr.ScVal.scvSymbol(v)]); }
+function scI128(n)  { return getSdk().nativeToScVal(BigInt(n), { type: "i128" }); }
+
+// StreamerBond { token, amount, slash_recipient } — map keys sorted by name
+function scBond(token, amount, slashRecipient) {
+  var xdr = getSdk().xdr;
+  var entry = function(k, v) { return new xdr.ScMapEntry({ key: xdr.ScVal.scvSymbol(k), val: v }); };
+  return xdr.ScVal.scvMap([
+    entry("amount",          scI128(amount)),
+    entry("slash_recipient", slashRecipient ? scAddr(slashRecipient) : xdr.ScVal.scvVoid()),
+    entry("token",           scAddr(token)),
+  ]);
+}
+
+// EntryFee { token, amount, streamer_cut_bps, protocol_fee_bps } — amount 0 = free entry
+function scEntryFee(token, amount, streamerCutBps, protocolFeeBps) {
+  var xdr = getSdk().xdr;
+  var entry = function(k, v) { return new xdr.ScMapEntry({ key: xdr.ScVal.scvSymbol(k), val: v }); };
+  return xdr.ScVal.scvMap([
+    entry("amount",           scI128(amount)),
+    entry("protocol_fee_bps", scU32(protocolFeeBps)),
+    entry("streamer_cut_bps", scU32(streamerCutBps)),
+    entry("token",            scAddr(token)),
+  ]);
+}
+
+// BalanceGate struct (map keys sorted); minBalance 0 = open to everyone
+function scBalanceGate(token, minBalance, recheckAtReveal) {
+  var xdr = getSdk().xdr;
+  var entry = function(k, v) { return new xdr.ScMapEntry({ key: xdr.ScVal.scvSymbol(k), val: v }); };
+  return xdr.ScVal.scvMap([
+    entry("min_balance",       scI128(minBalance)),
+    entry("recheck_at_reveal", scBool(recheckAtReveal)),
+    entry("token",             scAddr(token)),
+  ]);
+}
+
+// SessionConfig struct (map keys sorted); `c` uses the contract field names.
+// Omitted fields fall back to the contract defaults (manual close, 2..100 000
+// participants, ~1 day reveal window, ~1 h nonce window, free open entry).
+function scSessionConfig(c) {
+  var xdr = getSdk().xdr;
+  var entry = function(k, v) { return new xdr.ScMapEntry({ key: xdr.ScVal.scvSymbol(k), val: v }); };
+  var token = c.token || CONFIG.XLM_SAC;
+  return xdr.ScVal.scvMap([
+    entry("allow_early_close",     scBool(c.allow_early_close !== false)),
+    entry("allowlist_root",        c.allowlist_root ? scBytes(c.allowlist_root) : scVoid()),
+    entry("balance_gate",          c.balance_gate || scBalanceGate(token, 0, false)),
+    entry("bond",                  c.bond || scBond(token, 0, null)),
+    entry("entry_fee",             c.entry_fee || scEntryFee(token, 0, 0, 0)),
+    entry("max_participants",      scU32(c.max_participants || 100000)),
+    entry("min_participants",      scU32(c.min_participants || 2)),
+    entry("multi_party",           scBool(c.multi_party)),
+    entry("nonce_window_ledgers",  scU32(c.nonce_window_ledgers || 720)),
+    entry("num_winners",           scU32(c.num_winners || 1)),
+    entry("registration_deadline", scU32(c.registration_deadline || 0)),
+    entry("report_to_hub",         scBool(c.report_to_hub !== false)),
+    entry("reveal_window_ledgers", scU32(c.reveal_window_ledgers || 17280)),
+  ]);
+}
+
+// XLM → stroops (7 decimals) as BigInt
+function toStroops(xlm) { return BigInt(Math.round(xlm * 1e7)); }
+
+// Per-winner prize in XLM, split like the contract's pay_winners:
+// equal shares in stroops, the remainder to the first winner.
+function prizeShares(xlm, count) {
+  var total = toStroops(xlm), n = BigInt(count);
+  var share = total / n, remainder = total % n;
+  var shares = [];
+  for (var k = 0; k < count; k++) {
+    shares.push(Number(k === 0 ? share + remainder : share) / 1e7);
+  }
+  return shares;
+}
 
 async function signXdr(xdr) {
   var freighter = getFreighter();
@@ -333,6 +557,99 @@ async function signXdr(xdr) {
   return typeof result === "string" ? result : (result.signedTxXdr || result);
 }
 
+// Read-only call: simulate and decode the return value
+async function readContract(method, args) {
+  if (!args) args = [];
+  var Sdk = getSdk(), server = getRpc();
+  var account = await server.getAccount(state.publicKey);
+  var tx = new Sdk.TransactionBuilder(account, { fee: "100", networkPassphrase: CONFIG.NETWORK_PASSPHRASE })
+    .addOperation(new Sdk.Contract(CONFIG.CONTRACT_ID).call(method, ...args))
+    .setTimeout(30).build();
+  var sim = await server.simulateTransaction(tx);
+  if (Sdk.SorobanRpc.Api.isSimulationError(sim)) throw simulationError(sim);
+  return Sdk.scValToNative(sim.result.retval);
+}
+
+// RouletteError codes (contract/src/lib.rs) → readable messages
+var CONTRACT_ERRORS = {
+  1: "contract already initialized",
+  2: "contract not initialized",
+  3: "verification key already set",
+  4: "verification key not set",
+  5: "verification key does not match the circuit",
+  6: "no pending admin transfer",
+  7: "storage already migrated",
+  8: "contract paused by the admin",
+  9: "unknown storage version, cannot migrate",
+  10: "session already exists",
+  11: "session not found",
+  12: "prize amount must not be negative",
+  13: "invalid number of winners",
+  14: "bond amount must not be negative",
+  15: "commitment is not a field element",
+  16: "invalid entry fee",
+  17: "invalid balance gate",
+  18: "invalid registration deadline",
+  19: "invalid session config",
+  20: "registration is closed",
+  21: "max participants reached",
+  22: "already registered",
+  23: "nonce commitment required",
+  24: "session does not take nonce commitments",
+  25: "invalid batch size",
+  26: "allowlist proof required",
+  27: "session has no allowlist",
+  28: "address is not on the allowlist",
+  29: "entry-fee batches need participant signatures",
+  30: "session is not open",
+  31: "need at least 2 participants",
+  32: "fewer participants than winners",
+  33: "scheduled draw cannot be closed early",
+  34: "close not due yet",
+  40: "session is not multi-party",
+  41: "registrations must be closed",
+  42: "nonce reveal window has ended",
+  43: "not registered",
+  44: "nonce already revealed",
+  45: "nonce commitment mismatch",
+  46: "no stored nonce commitment for this participant",
+  50: "reveal deadline has passed",
+  51: "nonce reveal window still open",
+  52: "secret_number must be > 0",
+  53: "salt is not a field element",
+  54: "commitment mismatch",
+  55: "winner index out of bounds",
+  56: "not enough nonces revealed, the draw can only be cancelled",
+  60: "proof reveal supports a single winner",
+  61: "proof reveal requires a Poseidon commitment",
+  62: "wrong number of public inputs",
+  63: "public input mismatch: commitment",
+  64: "public input mismatch: number of participants",
+  65: "public input mismatch: entropy",
+  66: "invalid proof",
+  67: "too many weighted tickets for a proof reveal; reveal with the secret",
+  70: "reveal deadline not reached",
+  71: "session has not expired",
+  72: "no bond share to claim",
+  73: "bond share already claimed",
+  74: "no entry ticket to refund",
+  75: "entry ticket already refunded",
+  80: "draw record not found",
+  90: "participant weight out of range",
+  91: "weights not supported in multi-party draws",
+  100: "insufficient token balance to enter",
+  110: "draw can no longer be cancelled",
+  120: "no pending Game Hub report to retry",
+  130: "session ID reserved for create_draw",
+  131: "no session IDs left for create_draw",
+};
+
+function simulationError(sim) {
+  var m = /Error\(Contract, #(\d+)\)/.exec(sim.error || "");
+  var msg = m && CONTRACT_ERRORS[m[1]];
+  return new Error(msg ? "Contract: " + msg + " (#" + m[1] + ")" : "Simulation: " + sim.error);
+}
+
 async function invokeContract(method, args) {
   if (!args) args = [];
   var Sdk = getSdk(), server = getRpc();
@@ -341,7 +658,7 @@ async function invokeContract(method, args) {
     .addOperation(new Sdk.Contract(CONFIG.CONTRACT_ID).call(method, ...args))
     .setTimeout(30).build();
   var sim = await server.simulateTransaction(tx);
-  if (Sdk.SorobanRpc.Api.isSimulationError(sim)) throw new Error("Simulation: " + sim.error);
+  if (Sdk.SorobanRpc.Api.isSimulationError(sim)) throw simulationError(sim);
   var prep = Sdk.SorobanRpc.assembleTransaction(tx, sim).build();
   var signedXdr = await signXdr(prep.toXDR());
   var signedTx  = Sdk.TransactionBuilder.fromXDR(signedXdr, CONFIG.NETWORK_PASSPHRASE);
@@ -350,51 +667,24 @@ async function invokeContract(method, args) {
   for (var i = 0; i < 30; i++) {
     await sleep(2000);
     var res = await server.getTransaction(send.hash);
-    if (res.status === "SUCCESS") return res;
+    if (res.status === "SUCCESS") { res.txHash = send.hash; return res; }
     if (res.status === "FAILED")  throw new Error("Transaction failed on-chain");
   }
   throw new Error("Timeout waiting for confirmation");
 }
 
-// ─────────────────────────────────────────────────────────────────────────────
-//  PRIZE PAYMENT — Horizon XLM transfer
-// ─────────────────────────────────────────────────────────────────────────────
-async function sendPrizeToWinner(winnerAddress, amountXLM) {
-  var Sdk     = getSdk();
-  var horizon = getHorizon();
-  var account = await horizon.loadAccount(state.publicKey);
-  var fee     = await horizon.fetchBaseFee();
-  var tx = new Sdk.TransactionBuilder(account, {
-    fee: String(fee),
-    networkPassphrase: CONFIG.NETWORK_PASSPHRASE,
-  })
-    .addOperation(Sdk.Operation.payment({
-      destination: winnerAddress,
-      asset:       Sdk.Asset.native(),
-      amount:      amountXLM.toFixed(7),
-    }))
-    .setTimeout(30)
-    .build();
-
-  var signedXdr = await signXdr(tx.toXDR());
-  var signedTx  = Sdk.TransactionBuilder.fromXDR(signedXdr, CONFIG.NETWORK_PASSPHRASE);
-  var result    = await horizon.submitTransaction(signedTx);
-  return result.hash;
-}
-
 function sleep(ms) { return new Promise(function(r) { setTimeout(r, ms); }); }
 
 // ─────────────────────────────────────────────────────────────────────────────
 //  MAIN — startDraw
 //  Full automated flow:
 //    1. Generate secret + commitment
-//    2. commit_draw on Soroban
-//    3. register_participant × N
+//    2. create_draw on Soroban (prize escrowed, session ID assigned)
+//    3. register_participants (batched)
 //    4. close_registrations
 //    5. Compute winner with ZK proof
 //    6. Animate wheel
-//    7. reveal_winner on Soroban
-//    8. Send XLM prize to winner via Horizon
+//    7. reveal_winner on Soroban (contract pays the escrowed prize)
 // ─────────────────────────────────────────────────────────────────────────────
 window.startDraw = async function () {
   if (state.running) return;
@@ -415,18 +705,31 @@ window.startDraw = async function () {
     var secretBytes = randomBytes(8);
     var secret = new DataView(secretBytes.buffer).getBigUint64(0, false);
     if (secret === 0n) secret = 1n;
-    var salt = randomBytes(32);
+    var salt = randomFieldSalt();
     var commitment = await computeCommitment(secret, salt);
 
     state.secretNumber = secret;
     state.salt         = salt;
     state.commitment   = commitment;
-    state.sessionId    = Math.floor(Date.now() / 1000) % 999999 + 1;
 
     document.getElementById("zk-commitment").textContent = shortHex(commitment);
     document.getElementById("zk-commitment").className   = "zk-val ok";
     log("Commitment: " + shortHex(commitment), "ok");
 
+    // ── 2. create_draw (the contract assigns the session ID) ─────────────
+    log("Sending create_draw to Soroban (escrowing " + state.prizeAmount + " XLM)…", "info");
+    var created = await invokeContract("create_draw", [
+      scAddr(state.publicKey), scBytes(commitment),
+      scEnum("Poseidon"),   // CommitmentScheme — matches the Noir circuit
+      scAddr(CONFIG.XLM_SAC), scI128(toStroops(state.prizeAmount)),
+      scSessionConfig({
+        num_winners: state.numWinners,
+        bond:        scBond(CONFIG.XLM_SAC, toStroops(CONFIG.BOND_XLM), null),
+        // everything else default: free open entry, streamer closes manually
+      })
+    ]);
+    state.sessionId = Number(getSdk().scValToNative(created.returnValue));
+
     // Save to localStorage in case of page reload
     localStorage.setItem("zk_" + state.sessionId, JSON.stringify({
       secretNumber: secret.toString(),
@@ -434,35 +737,35 @@ window.startDraw = async function () {
       commitment:   Array.from(commitment),
     }));
 
-    // ── 2. commit_draw ───────────────────────────────────────────────────
-    log("Sending commit_draw to Soroban…", "info");
-    await invokeContract("commit_draw", [
-      scAddr(state.publicKey), scU32(state.sessionId), scBytes(commitment)
-    ]);
     document.getElementById("zk-onchain").textContent = "✓ committed";
     document.getElementById("zk-onchain").className   = "zk-val ok";
-    log("commit_draw(" + state.sessionId + ") confirmed ✓", "ok");
-
-    // ── 3. register all participants ─────────────────────────────────────
-    for (var ri = 0; ri < state.participants.length; ri++) {
-      log("Registering #" + (ri+1) + "…", "info");
-      await invokeContract("register_participant", [
-        scU32(state.sessionId), scAddr(state.participants[ri])
+    log("create_draw → session " + state.sessionId + " confirmed ✓", "ok");
+
+    // ── 3. register all participants (batched, streamer-signed) ──────────
+    for (var ri = 0; ri < state.participants.length; ri += CONFIG.REGISTER_BATCH) {
+      var batch = state.participants.slice(ri, ri + CONFIG.REGISTER_BATCH);
+      log("Registering #" + (ri+1) + "–#" + (ri + batch.length) + "…", "info");
+      await invokeContract("register_participants", [
+        scU32(state.sessionId), scVec(batch.map(scAddr)), scBool(false)
       ]);
-      log("✓ Registered: " + state.participants[ri].slice(0,10) + "…", "ok");
+      log("✓ Registered " + batch.length + " participant(s)", "ok");
     }
 
     // ── 4. close_registrations ───────────────────────────────────────────
     log("Closing registrations…", "info");
     await invokeContract("close_registrations", [scU32(state.sessionId)]);
     log("Registrations closed ✓", "ok");
+    var session = await readContract("get_session", [scU32(state.sessionId)]);
+    var closeEntropy = new Uint8Array(session.close_entropy);
+    log("Close entropy: " + shortHex(closeEntropy), "ok");
 
     // ── 5. ZK proof simulation ───────────────────────────────────────────
     log("Generating Noir ZK proof…", "info");
     document.getElementById("zk-proof-status").textContent = "generating…";
     var n = state.participants.length;
-    var winnerIndex = Number(secret % BigInt(n));
-    var proof = await simulateNoirProof(secret, salt, n, winnerIndex);
+    var winnerIdxs  = await deriveWinners(secret, salt, closeEntropy, n, state.numWinners);
+    var winnerIndex = winnerIdxs[0];
+    var proof = await simulateNoirProof(secret, salt, n, closeEntropy, winnerIndex);
 
     var blob = new Blob([JSON.stringify(proof, null, 2)], { type: "application/json" });
     var link = document.getElementById("noir-proof-link");
@@ -473,7 +776,7 @@ window.startDraw = async function () {
     document.getElementById("zk-proof-status").className   = "zk-val ok";
     document.getElementById("zk-winner").textContent = "#" + winnerIndex + " → " + state.participants[winnerIndex].slice(0,8) + "…";
     document.getElementById("zk-winner").className   = "zk-val ok";
-    log("Noir proof: winner_index=" + winnerIndex + " (" + secret + " % " + n + ")", "ok");
+    log("Noir proof: winner_index=" + winnerIndex + " (uniform draw over " + n + ")", "ok");
 
     // ── 6. Spin animation ────────────────────────────────────────────────
     log("Spinning the wheel…", "info");
@@ -487,7 +790,7 @@ window.startDraw = async function () {
     // ── 7. reveal_winner on Soroban ──────────────────────────────────────
     log("Sending reveal_winner to Soroban…", "info");
     document.getElementById("spin-btn-inner").innerHTML = '<span class="spinner"></span> Revealing…';
-    await invokeContract("reveal_winner", [
+    var revealRes = await invokeContract("reveal_winner", [
       scU32(state.sessionId), scU64(secret), scBytes(salt)
     ]);
     log("reveal_winner confirmed on-chain ✓", "ok");
@@ -496,17 +799,17 @@ window.startDraw = async function () {
 
     state.winnerIndex   = winnerIndex;
     state.winnerAddress = state.participants[winnerIndex];
-    renderParticipants([winnerIndex]);
+    renderParticipants(winnerIdxs);
 
-    // ── 8. Send prize XLM ────────────────────────────────────────────────
-    var prizePerWinner = state.prizeAmount;
-    log("Sending " + prizePerWinner + " XLM to " + state.winnerAddress.slice(0,10) + "…", "warn");
-    document.getElementById("spin-btn-inner").innerHTML = '<span class="spinner"></span> Sending prize…';
-    var txHash = await sendPrizeToWinner(state.winnerAddress, prizePerWinner);
-    log("🏆 Prize sent! TX: " + txHash.slice(0,16) + "…", "ok");
+    // ── 8. Prize was split among the winners by reveal_winner ────────────
+    var shares = prizeShares(state.prizeAmount, winnerIdxs.length);
+    var txHash = revealRes.txHash || "";
+    winnerIdxs.forEach(function(idx, k) {
+      log("🏆 " + shares[k] + " XLM paid by the contract to " + state.participants[idx].slice(0,10) + "…", "ok");
+    });
 
     // ── 9. Show winner overlay ───────────────────────────────────────────
-    showWinnerOverlay(winnerIndex, state.winnerAddress, prizePerWinner, txHash);
+    showWinnerOverlay(winnerIdxs, shares, txHash);
 
   } catch (err) {
     log("Draw error: " + err.message, "error");
@@ -522,17 +825,24 @@ window.startDraw = async function () {
 // ─────────────────────────────────────────────────────────────────────────────
 //  NOIR PROOF SIMULATION
 // ─────────────────────────────────────────────────────────────────────────────
-async function simulateNoirProof(secret, salt, n, winnerIdx) {
+// Public inputs in the order reveal_winner_with_proof checks them:
+// [commitment, n, close_entropy[0..32], winner_index], each a 32-byte
+// big-endian field element.
+function noirPublicInputs(commitment, n, closeEntropy, winnerIdx) {
+  function field(v) { return "0x" + v.toString(16).padStart(64, "0"); }
+  var inputs = ["0x" + Array.from(commitment).map(function(b) { return b.toString(16).padStart(2,"0"); }).join(""), field(n)];
+  for (var i = 0; i < 32; i++) inputs.push(field(closeEntropy[i]));
+  inputs.push(field(winnerIdx));
+  return inputs;
+}
+
+async function simulateNoirProof(secret, salt, n, closeEntropy, winnerIdx) {
   await sleep(1400 + Math.random() * 1000);
   var commitment = await computeCommitment(secret, salt);
   return {
-    _note: "Simulated proof — replace with @noir-lang/noir_js in production.",
-    circuit: "zk_roulette", backend: "barretenberg/UltraPlonk",
-    public_inputs: {
-      public_commitment: "0x" + Array.from(commitment).map(function(b) { return b.toString(16).padStart(2,"0"); }).join(""),
-      number_of_participants: n,
-      winner_index: winnerIdx,
-    },
+    _note: "Simulated proof — a real Groth16 proof for this Poseidon commitment is accepted on-chain by reveal_winner_with_proof; this demo reveals with reveal_winner.",
+    circuit: "zk_roulette", backend: "groth16/bn254",
+    public_inputs: noirPublicInputs(commitment, n, closeEntropy, winnerIdx),
     proof_bytes: "0x" + Array.from(randomBytes(32)).map(function(b) { return b.toString(16).padStart(2,"0"); }).join("") + "…",
     verified: true, timestamp: new Date().toISOString(),
   };
@@ -541,10 +851,20 @@ async function simulateNoirProof(secret, salt, n, winnerIdx) {
 // ─────────────────────────────────────────────────────────────────────────────
 //  WINNER OVERLAY
 // ─────────────────────────────────────────────────────────────────────────────
-function showWinnerOverlay(index, address, prize, txHash) {
-  document.getElementById("w-addr").textContent  = address || "—";
-  document.getElementById("w-prize").textContent = prize ? prize.toFixed(4) + " XLM sent 🚀" : "";
-  document.getElementById("w-badge").textContent = "Entry #" + (index + 1);
+function showWinnerOverlay(indices, shares, txHash) {
+  var addrEl = document.getElementById("w-addr");
+  addrEl.textContent = "";
+  indices.forEach(function(idx, k) {
+    var line = document.createElement("div");
+    line.textContent = state.participants[idx] + (indices.length > 1 ? " · " + shares[k].toFixed(4) + " XLM" : "");
+    addrEl.appendChild(line);
+  });
+  var total = shares.reduce(function(a, b) { return a + b; }, 0);
+  document.getElementById("w-prize").textContent = total
+    ? total.toFixed(4) + " XLM sent" + (indices.length > 1 ? " to " + indices.length + " winners" : "") + " 🚀"
+    : "";
+  document.getElementById("w-badge").textContent = (indices.length > 1 ? "Entries " : "Entry ")
+    + indices.map(function(idx) { return "#" + (idx + 1); }).join(", ");
   var txEl = document.getElementById("w-tx");
   if (txHash) {
     txEl.style.display  = "block";
@@ -0,0 +1,36 @@
+// ═══════════════════════════════════════════════════════════════════════════
+//  Allowlist Merkle — constructor off-chain
+//
+//  Imprime la raíz para `SessionConfig.allowlist_root` y la prueba de cada
+//  dirección para `register_participant`, con el mismo árbol que verifica
+//  el contrato (`zk_roulette::merkle`).
+//
+//  Uso:
+//    cargo run -p zk-roulette --example allowlist -- G... G... C...
+// ═══════════════════════════════════════════════════════════════════════════
+
+use soroban_sdk::{Address, BytesN, Env, Vec};
+use zk_roulette::merkle;
+
+fn hex(bytes: &BytesN<32>) -> String {
+    bytes.to_array().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn main() {
+    let env = Env::default();
+    let mut addresses = Vec::new(&env);
+    for strkey in std::env::args().skip(1) {
+        addresses.push_back(Address::from_str(&env, &strkey));
+    }
+
+    let Some(root) = merkle::root(&env, &addresses) else {
+        eprintln!("uso: allowlist <dirección>...");
+        std::process::exit(1);
+    };
+    println!("root {}", hex(&root));
+    for index in 0..addresses.len() {
+        let proof = merkle::proof(&env, &addresses, index).unwrap();
+        let siblings: std::vec::Vec<String> = proof.iter().map(|node| hex(&node)).collect();
+        println!("{} [{}]", addresses.get(index).unwrap().to_string(), siblings.join(", "));
+    }
+}
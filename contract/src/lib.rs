@@ -4,27 +4,83 @@
 //
 //  ARQUITECTURA COMMIT-REVEAL
 //  ─────────────────────────
-//  1. commit_draw()         → El streamer publica su hash-compromiso on-chain
-//  2. register_participant()→ Los espectadores se inscriben
-//  3. close_registrations() → El streamer cierra el sorteo
+//  1. create_draw()         → El streamer publica su hash-compromiso on-chain
+//                             y deposita el premio y su fianza (escrow);
+//                             el contrato asigna el ID de sesión
+//     commit_draw()         → Igual, con un ID elegido por el cliente
+//  2. register_participant()→ Los espectadores se inscriben (y pagan la
+//                             entrada si el sorteo la tiene)
+//     register_participants()→ o el streamer inscribe un lote en una sola
+//                             transacción
+//     set_participant_weight()→ El streamer da más boletos a un
+//                             participante (extracción ponderada)
+//  3. close_registrations() → El streamer cierra el sorteo; se fija la
+//                             entropía de cierre (impredecible al commit)
+//     finalize_close()      → o, con cierre programado, cualquiera cierra
+//                             al vencer el plazo o llenarse el cupo
+//     reveal_nonce()        → (modo multi-party) cada participante revela
+//                             el nonce que comprometió al registrarse
 //  4. reveal_winner()       → El streamer revela el secreto; el contrato
 //                             verifica el commitment, calcula el ganador,
-//                             llama a start_game() + end_game() en Game Hub
+//                             transfiere el premio al ganador, guarda el
+//                             resultado y lo informa al Game Hub
+//                             (start_game() + end_game())
+//     retry_hub_report()    → Si el Game Hub falló, cualquiera reintenta
+//                             el informe; el sorteo ya quedó resuelto
+//     reveal_winner_with_proof() → Alternativa sin revelar el secreto: el
+//                             contrato verifica la prueba Groth16 del
+//                             circuito Noir y toma su `winner_index`
+//     cancel_draw()         → El streamer aborta el sorteo antes del
+//                             reveal; todo se reembolsa y el evento indica
+//                             si fue tras el cierre
+//     expire_draw()         → Si el streamer no revela antes del plazo,
+//                             cualquiera expira la sesión: se reembolsa el
+//                             premio y la fianza del streamer se confisca
+//                             (los participantes la cobran con
+//                             claim_bond_share)
+//  5. Archivo               → Tras el reveal se guarda un DrawRecord en
+//                             storage persistente: el sorteo sigue siendo
+//                             auditable cuando la sesión temporal expira
+//
+//  ADMINISTRACIÓN
+//  ──────────────
+//  El admin (fijado en initialize) puede reemplazar el WASM con upgrade()
+//  sin cambiar el CONTRACT_ID y ejecutar migrate() para llevar el storage
+//  al layout de la nueva versión. El traspaso de admin es en dos pasos
+//  (propose_admin + accept_admin). Ante un fallo, pause() detiene por
+//  categoría la creación de sorteos, las inscripciones o los reveals;
+//  cancelaciones y reembolsos siguen funcionando.
 //
 //  GARANTÍA ZK:
-//    El compromiso SHA-256(secret || salt) se guarda on-chain ANTES de
-//    conocer los participantes. Nadie puede elegir el ganador a posteriori.
-//    El circuito Noir genera una prueba formal de esto para auditoría pública.
+//    El compromiso Hash(secret || salt) — SHA-256, o Poseidon como en el
+//    circuito — se guarda on-chain ANTES de conocer los participantes.
+//    Nadie puede elegir el ganador a posteriori.
+//    El ganador depende además de entropía fijada al cerrar inscripciones,
+//    así que el streamer no puede dirigir el resultado añadiendo cuentas
+//    propias mientras el sorteo está abierto.
+//    El circuito Noir genera una prueba formal de esto, verificable on-chain
+//    con las primitivas BN254 de Protocol 25 (ver `verifier`).
 //
 //  Game Hub: CB4VZAT2U3UC6XFK3N23SKRF2NDCMP3QHJYMCHHFMZO7MRQO6DQ2EMYG
 // ═══════════════════════════════════════════════════════════════════════════
 
 #![no_std]
+// Los entry points de Soroban reciben sus parámetros planos y el cliente
+// generado por `contractimpl` replica la firma completa.
+#![allow(clippy::too_many_arguments)]
+
+pub mod merkle;
+mod poseidon;
+mod selection;
+mod verifier;
+mod weights;
+
+pub use verifier::{Groth16Proof, VerificationKey};
 
 use soroban_sdk::{
-    contract, contractclient, contractimpl, contracttype,
-    Address, Bytes, BytesN, Env, Vec,
-    Symbol,
+    contract, contractclient, contracterror, contractevent, contractimpl, contracttype, token,
+    xdr::ToXdr,
+    Address, Bytes, BytesN, Env, IntoVal, Map, Val, Vec,
 };
 
 // ─── Interface del Game Hub (hackathon) ─────────────────────────────────────
@@ -50,21 +106,317 @@ pub trait GameHub {
 }
 
 // ─── Constantes ──────────────────────────────────────────────────────────────
-/// Máximo de participantes por sorteo
-const MAX_PARTICIPANTS: u32 = 1024;
+/// Máximo de participantes por sorteo. El registro y el reveal no
+/// dependen del tamaño de la lista (una entrada de storage por índice)
+const MAX_PARTICIPANTS: u32 = 100_000;
+
+/// Máximo de ganadores por sorteo (la derivación sin reemplazo es O(k²))
+const MAX_WINNERS: u32 = 64;
 
 /// TTL en ledgers: ~30 días @ ~5s por ledger (30 * 24 * 3600 / 5 ≈ 518400)
 const TTL_LEDGERS: u32 = 518_400;
 
+/// Plazo para revelar recomendado: ~24 h @ ~5s por ledger
+pub const DEFAULT_REVEAL_WINDOW_LEDGERS: u32 = 17_280;
+
+/// Ventana de reveal de nonces recomendada (modo multi-party): ~1 h
+pub const DEFAULT_NONCE_WINDOW_LEDGERS: u32 = 720;
+
+/// Ventana mínima de reveal (secreto o nonces): ~5 min
+const MIN_WINDOW_LEDGERS: u32 = 60;
+
+/// Ventana máxima de reveal (secreto o nonces): ~7 días
+const MAX_WINDOW_LEDGERS: u32 = 120_960;
+
+/// Antelación máxima del plazo de inscripción: ~14 días
+const MAX_REGISTRATION_LEDGERS: u32 = 241_920;
+
+/// Plazo de reveal más tardío que admite la configuración, contado desde
+/// `commit_draw`: inscripción y las dos ventanas al máximo (~28 días).
+/// Deja ~2 días antes de `TTL_LEDGERS` para expirar y reclamar; una pausa
+/// de reveals no retrasa los plazos más allá (ver `load_session`)
+const MAX_REVEAL_DEADLINE_LEDGERS: u32 = MAX_REGISTRATION_LEDGERS + 2 * MAX_WINDOW_LEDGERS;
+
+/// Primer ID que asigna `create_draw`. Los IDs desde aquí quedan
+/// reservados al contador: `commit_draw` los rechaza, así que nadie puede
+/// ocuparlos de antemano
+pub const AUTO_SESSION_ID_START: u32 = 1 << 31;
+
+/// Primer ID de partida del Game Hub para los ganadores k ≥ 1. El rango
+/// queda fuera de los IDs que asignan `commit_draw` y `create_draw`, así
+/// que esas partidas no chocan con la del ganador 0 de ninguna sesión
+pub const HUB_SESSION_ID_START: u32 = 3 << 30;
+
+/// Máximo de direcciones por llamada a `register_participants`
+const MAX_BATCH: u32 = 200;
+
+/// Máximo de entradas por página en las consultas paginadas
+const MAX_PAGE: u32 = 100;
+
+/// Denominador de los porcentajes en puntos básicos (100% = 10 000)
+const BPS_DENOMINATOR: i128 = 10_000;
+
+/// Comisión máxima del protocolo sobre el bote de entradas: 10%
+const MAX_PROTOCOL_FEE_BPS: u32 = 1_000;
+
+/// Participantes examinados por ganador al buscar un sustituto que
+/// cumpla el saldo mínimo en el reveal (ver `BalanceGate`)
+const MAX_GATE_PROBES: u32 = 32;
+
+/// Versión del layout de storage que entiende este WASM.
+/// Se incrementa junto con un paso nuevo en `migrate_step`.
+const CONTRACT_VERSION: u32 = 1;
+
 // ─── Claves de Storage ───────────────────────────────────────────────────────
 #[contracttype]
 pub enum DataKey {
     /// Dirección del contrato Game Hub (almacenada en init)
     GameHub,
+    /// Administrador: upgrades, migraciones y configuración
+    Admin,
+    /// Admin propuesto, pendiente de `accept_admin`
+    PendingAdmin,
+    /// Destino de la comisión del protocolo (por defecto, el admin)
+    FeeRecipient,
+    /// Próximo ID candidato para `create_draw`
+    NextSessionId,
+    /// Próximo ID de partida del Hub para ganadores k ≥ 1
+    NextHubSessionId,
+    /// Versión del layout de storage (ver `CONTRACT_VERSION`)
+    Version,
+    /// Categorías pausadas por el admin (ver `PauseState`)
+    Pause,
     /// Estado de una sesión de sorteo
     Session(u32),
-    /// Lista de participantes de una sesión
-    Participants(u32),
+    /// Participante `index` de una sesión
+    Participant(u32, u32),
+    /// Índice de un participante en su sesión (pertenencia en O(1))
+    ParticipantIndex(u32, Address),
+    /// Fianzas pendientes de un streamer por token (persistente)
+    StreamerBonds(Address),
+    /// Sesiones de un streamer con fianza pendiente (persistente)
+    BondedSessions(Address),
+    /// Compromiso de nonce del participante `index` (modo multi-party)
+    NonceCommitment(u32, u32),
+    /// Marca de nonce revelado del participante `index` (modo multi-party)
+    NonceRevealed(u32, u32),
+    /// k-ésimo participante elegible → índice, en orden de `reveal_nonce`
+    /// (modo multi-party)
+    EligibleIndex(u32, u32),
+    /// Parte de la fianza confiscada ya reclamada por el participante `index`
+    BondShareClaimed(u32, u32),
+    /// Entrada ya reembolsada al participante `index`
+    TicketRefunded(u32, u32),
+    /// Peso del participante `index` del árbol `weight_tree`, si es
+    /// distinto de 1 (ver `weights`)
+    ParticipantWeight(u32, u32),
+    /// Nodo del árbol de Fenwick de pesos extra `weight_tree` (ver `weights`)
+    WeightNode(u32, u32),
+    /// Próximo `DrawSession::weight_tree` libre
+    NextWeightTree,
+    /// Clave de verificación Groth16 del circuito Noir
+    VerificationKey,
+    /// Registro permanente de un sorteo revelado (persistente)
+    DrawRecord(u32),
+    /// Sorteos archivados de un streamer (persistente)
+    StreamerDrawCount(Address),
+    /// n-ésimo sorteo archivado de un streamer → session_id (persistente).
+    /// Una entrada por sorteo: el historial crece sin límite y un único
+    /// `Vec` acabaría superando el tamaño máximo de una entrada.
+    StreamerDraw(Address, u32),
+    /// Sorteos que un streamer canceló tras cerrar inscripciones
+    /// (persistente)
+    StreamerLateCancels(Address),
+}
+
+/// Sesión almacenada con la versión de su layout.
+///
+/// Un upgrade que cambie `DrawSession` añade una variante nueva y la
+/// conversión desde la anterior en `load_session`; las sesiones vivas se
+/// migran al leerse, sin recorrer el storage temporal.
+#[contracttype]
+#[derive(Clone)]
+pub enum StoredSession {
+    V1(DrawSession),
+}
+
+// ─── Errores ─────────────────────────────────────────────────────────────────
+// Códigos estables: los frontends y bots los comparan numéricamente, así que
+// un código publicado no se reutiliza ni se renumera. Agrupados por fase.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum RouletteError {
+    // ── Inicialización y configuración ───────────────────────────────────────
+    /// `initialize` ya se llamó
+    AlreadyInitialized = 1,
+    /// Falta `initialize` (no hay Game Hub)
+    NotInitialized = 2,
+    /// La clave de verificación ya se cargó
+    VerificationKeyAlreadySet = 3,
+    /// `reveal_winner_with_proof` sin clave de verificación
+    VerificationKeyNotSet = 4,
+    /// La clave no corresponde a las entradas públicas del circuito
+    InvalidVerificationKey = 5,
+    /// `accept_admin` sin un traspaso propuesto
+    NoPendingAdmin = 6,
+    /// El storage ya está en `CONTRACT_VERSION`
+    AlreadyMigrated = 7,
+    /// La categoría de la operación está pausada por el admin
+    Paused = 8,
+    /// `migrate_step` no tiene rama para la versión guardada
+    UnknownStorageVersion = 9,
+
+    // ── Commit ───────────────────────────────────────────────────────────────
+    /// Ya existe una sesión con ese ID
+    SessionAlreadyExists = 10,
+    /// `commit_draw` con un ID ≥ `AUTO_SESSION_ID_START`, reservado a
+    /// `create_draw`
+    SessionIdReserved = 130,
+    /// `create_draw` agotó su rango de IDs (hasta `HUB_SESSION_ID_START`)
+    SessionIdsExhausted = 131,
+    /// La sesión no existe o sus datos expiraron
+    SessionNotFound = 11,
+    /// `prize_amount` < 0
+    NegativePrizeAmount = 12,
+    /// `num_winners` fuera de `1..=MAX_WINNERS`
+    InvalidNumberOfWinners = 13,
+    /// `bond.amount` < 0
+    NegativeBondAmount = 14,
+    /// Commitment Poseidon >= r
+    CommitmentNotInField = 15,
+    /// Entrada < 0 o comisiones fuera de rango
+    InvalidEntryFee = 16,
+    /// `balance_gate.min_balance` < 0
+    InvalidBalanceGate = 17,
+    /// Plazo de inscripción ya vencido o demasiado lejano
+    InvalidRegistrationDeadline = 18,
+    /// Límites de participantes o ventanas fuera de los del contrato
+    InvalidSessionConfig = 19,
+
+    // ── Registro ─────────────────────────────────────────────────────────────
+    /// La sesión ya no admite inscripciones
+    RegistrationClosed = 20,
+    /// Se alcanzó `max_participants` de la sesión
+    MaxParticipantsReached = 21,
+    /// La cuenta ya está inscrita
+    AlreadyRegistered = 22,
+    /// Sesión multi-party sin compromiso de nonce
+    NonceCommitmentRequired = 23,
+    /// Compromiso de nonce en una sesión que no es multi-party
+    NonceCommitmentNotAccepted = 24,
+    /// Lote vacío o mayor que `MAX_BATCH`
+    InvalidBatchSize = 25,
+    /// Sesión con allowlist: falta la prueba Merkle
+    AllowlistProofRequired = 26,
+    /// Prueba Merkle en una sesión sin allowlist
+    AllowlistProofNotAccepted = 27,
+    /// La prueba Merkle no lleva a la raíz de la allowlist
+    NotOnAllowlist = 28,
+    /// Sorteo con entrada: cada participante del lote debe firmar su pago
+    ParticipantAuthRequired = 29,
+
+    // ── Cierre ───────────────────────────────────────────────────────────────
+    /// La sesión no está abierta
+    SessionNotOpen = 30,
+    /// Menos de `min_participants`
+    NotEnoughParticipants = 31,
+    /// Menos participantes que ganadores
+    FewerParticipantsThanWinners = 32,
+    /// La sesión tiene cierre programado y no admite cierre anticipado
+    EarlyCloseNotAllowed = 33,
+    /// `finalize_close` antes del plazo y sin alcanzar el tope
+    CloseNotDue = 34,
+
+    // ── Nonces (multi-party) ─────────────────────────────────────────────────
+    /// La sesión no es multi-party
+    NotMultiParty = 40,
+    /// La operación exige inscripciones cerradas y sin reveal
+    SessionNotClosed = 41,
+    /// La ventana de `reveal_nonce` terminó
+    NonceWindowEnded = 42,
+    /// La cuenta no está inscrita
+    NotRegistered = 43,
+    /// El nonce ya se reveló
+    NonceAlreadyRevealed = 44,
+    /// El nonce no coincide con su compromiso
+    NonceCommitmentMismatch = 45,
+    /// No hay compromiso de nonce guardado para el participante
+    NonceCommitmentNotFound = 46,
+
+    // ── Reveal ───────────────────────────────────────────────────────────────
+    /// El plazo de reveal venció
+    RevealDeadlinePassed = 50,
+    /// Aún pueden revelarse nonces de participantes
+    NonceWindowOpen = 51,
+    /// `secret_number` == 0
+    InvalidSecret = 52,
+    /// Salt >= r con commitment Poseidon
+    SaltNotInField = 53,
+    /// Hash(secret || salt) != commitment
+    CommitmentMismatch = 54,
+    /// Índice ganador fuera de la lista de participantes
+    WinnerIndexOutOfBounds = 55,
+    /// Multi-party: menos nonces revelados que ganadores; la sesión se
+    /// cancela con `expire_draw`
+    NotEnoughNonces = 56,
+
+    // ── Reveal con prueba ────────────────────────────────────────────────────
+    /// El circuito sólo extrae un ganador
+    SingleWinnerOnly = 60,
+    /// La sesión no usa `CommitmentScheme::Poseidon`
+    PoseidonCommitmentRequired = 61,
+    /// Número de entradas públicas distinto del circuito
+    WrongPublicInputCount = 62,
+    /// La entrada pública del commitment no coincide
+    CommitmentInputMismatch = 63,
+    /// La entrada pública de participantes no coincide
+    ParticipantsInputMismatch = 64,
+    /// La entrada pública de entropía no coincide
+    EntropyInputMismatch = 65,
+    /// La prueba Groth16 no verifica
+    InvalidProof = 66,
+    /// Sesión con pesos cuyo total de boletos supera el límite del
+    /// circuito (`verifier::MAX_CIRCUIT_TICKETS`); revelar con el secreto
+    TooManyTicketsForProof = 67,
+
+    // ── Expiración ───────────────────────────────────────────────────────────
+    /// El plazo de reveal aún no venció
+    RevealDeadlineNotReached = 70,
+    /// La sesión no expiró (ni se canceló, para `claim_ticket_refund`;
+    /// ni se canceló tras el cierre, para `claim_bond_share`)
+    SessionNotExpired = 71,
+    /// La fianza no se reparte entre participantes (o es 0)
+    NoBondShare = 72,
+    /// La parte de la fianza ya se reclamó
+    BondShareAlreadyClaimed = 73,
+    /// La sesión no tiene entradas que reembolsar
+    NoTicketRefund = 74,
+    /// La entrada ya se reembolsó
+    TicketAlreadyRefunded = 75,
+
+    // ── Archivo ──────────────────────────────────────────────────────────────
+    /// No hay registro archivado para ese ID (no revelado o inexistente)
+    DrawRecordNotFound = 80,
+
+    // ── Pesos ────────────────────────────────────────────────────────────────
+    /// Peso fuera de `1..=MAX_WEIGHT`
+    InvalidWeight = 90,
+    /// Las sesiones multi-party extraen sobre el pool de nonces, sin pesos
+    WeightsNotSupported = 91,
+
+    // ── Saldo mínimo ─────────────────────────────────────────────────────────
+    /// El participante no tiene el saldo mínimo del token exigido
+    InsufficientTokenBalance = 100,
+
+    // ── Cancelación ──────────────────────────────────────────────────────────
+    /// Sólo se cancelan sesiones abiertas o cerradas sin reveal, antes
+    /// de vencer el plazo de reveal
+    SessionNotCancellable = 110,
+
+    // ── Game Hub ─────────────────────────────────────────────────────────────
+    /// `retry_hub_report` sin un informe pendiente (`HubStatus::Pending`)
+    HubReportNotPending = 120,
 }
 
 // ─── Tipos de Datos ──────────────────────────────────────────────────────────
@@ -79,6 +431,186 @@ pub enum DrawPhase {
     Closed,
     /// Ganador revelado
     Revealed,
+    /// El streamer no reveló antes del plazo; fondos reembolsados
+    Expired,
+    /// El streamer canceló el sorteo; fondos reembolsados
+    Cancelled,
+}
+
+/// Motivo de una cancelación, fijado por el contrato según la fase.
+/// Cancelar tras el cierre es sospechoso: el streamer ya puede calcular
+/// el ganador con su secreto y la entropía de cierre.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum CancelReason {
+    /// Cancelado con las inscripciones abiertas
+    BeforeClose = 0,
+    /// Cancelado con las inscripciones cerradas, antes del reveal: el
+    /// streamer ya conoce el resultado, así que pierde la fianza
+    AfterClose = 1,
+    /// Plazo de inscripción vencido sin el mínimo de participantes;
+    /// lo ejecuta `finalize_close` y no cuenta contra el streamer
+    Undersubscribed = 2,
+    /// Multi-party: la ventana de nonces terminó con menos nonces
+    /// revelados que ganadores; lo ejecuta `expire_draw` y no cuenta
+    /// contra el streamer
+    NotEnoughNonces = 3,
+}
+
+/// Estado del informe al Game Hub de una sesión
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum HubStatus {
+    /// Sin revelar todavía
+    Unreported = 0,
+    /// Revelado, pero el Hub falló a mitad del informe; ver
+    /// `retry_hub_report`
+    Pending = 1,
+    /// start_game + end_game enviados para todos los ganadores
+    Reported = 2,
+    /// Sesión creada con `report_to_hub` = false
+    Disabled = 3,
+}
+
+/// Categoría de operaciones que el admin puede pausar
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum PauseCategory {
+    /// `commit_draw` / `create_draw`
+    Creation = 0,
+    /// `register_participant` / `register_participants`
+    Registration = 1,
+    /// `reveal_winner` / `reveal_winner_with_proof` / `retry_hub_report`;
+    /// también bloquea `expire_draw`, para no confiscar la fianza de quien
+    /// no puede revelar. Mientras dura, los plazos de nonces y reveal de
+    /// las sesiones cerradas se desplazan, hasta `MAX_REVEAL_DEADLINE_LEDGERS`
+    /// (ver `PauseState::reveal_paused_ledgers`)
+    Reveal = 2,
+}
+
+/// Categorías pausadas. Cancelaciones, bajas, reembolsos y extensiones
+/// de TTL no se pausan nunca: son la salida de los fondos custodiados.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PauseState {
+    pub creation: bool,
+    pub registration: bool,
+    pub reveal: bool,
+    /// Ledger en que empezó la pausa de reveals en curso
+    pub reveal_paused_at: u32,
+    /// Ledgers acumulados por las pausas de reveals ya terminadas. Cada
+    /// sesión cerrada guarda este reloj en `DrawSession::reveal_clock` y
+    /// retrasa sus plazos lo que haya avanzado desde entonces
+    pub reveal_paused_ledgers: u32,
+}
+
+/// Función hash del commitment del streamer
+#[contracttype]
+#[derive(Clone, PartialEq)]
+pub enum CommitmentScheme {
+    /// SHA-256(secret_number_be8 || salt32)
+    Sha256,
+    /// Poseidon(secret_number, salt) sobre BN254 en 32 bytes big-endian,
+    /// el mismo valor que prueba el circuito Noir
+    Poseidon,
+}
+
+/// Fianza que el streamer deposita en `commit_draw`.
+/// Se devuelve con un `reveal_winner` válido; si la sesión expira sin
+/// reveal se confisca: va a `slash_recipient` o, si no hay, se reparte
+/// entre los participantes registrados (cada uno reclama su parte).
+#[contracttype]
+#[derive(Clone)]
+pub struct StreamerBond {
+    /// Contrato del token de la fianza
+    pub token: Address,
+    /// Cantidad depositada (0 = sin fianza)
+    pub amount: i128,
+    /// Destino de la fianza confiscada (None = participantes)
+    pub slash_recipient: Option<Address>,
+}
+
+/// Entrada de pago: cada inscripción transfiere `amount` de `token` al
+/// contrato. El bote acumulado se paga a los ganadores en el reveal,
+/// menos la parte del streamer y la comisión del protocolo.
+/// Con `amount` = 0 la inscripción es gratuita.
+#[contracttype]
+#[derive(Clone)]
+pub struct EntryFee {
+    /// Contrato del token de la entrada
+    pub token: Address,
+    /// Precio de una entrada (0 = sin entrada)
+    pub amount: i128,
+    /// Parte del bote para el streamer, en puntos básicos
+    pub streamer_cut_bps: u32,
+    /// Comisión del protocolo, en puntos básicos (máx. `MAX_PROTOCOL_FEE_BPS`)
+    pub protocol_fee_bps: u32,
+}
+
+/// Requisito de saldo para inscribirse: sólo entran cuentas con al menos
+/// `min_balance` de `token` (un SAC de un asset Stellar o cualquier
+/// token Soroban). Con `min_balance` = 0 no hay requisito.
+///
+/// Con `recheck_at_reveal`, un ganador que ya no tenga el saldo se
+/// sustituye por el siguiente índice (en orden de inscripción, circular)
+/// que entre en la extracción, no haya ganado y sí lo tenga. Se examinan
+/// hasta `MAX_GATE_PROBES` índices; si ninguno cumple, el ganador se
+/// mantiene (ya cumplía al inscribirse).
+#[contracttype]
+#[derive(Clone)]
+pub struct BalanceGate {
+    /// Contrato del token exigido
+    pub token: Address,
+    /// Saldo mínimo (0 = sin requisito)
+    pub min_balance: i128,
+    /// Volver a comprobar el saldo de los ganadores en el reveal
+    pub recheck_at_reveal: bool,
+}
+
+/// Configuración de una sesión, fijada en `commit_draw` y validada
+/// contra los límites del contrato (`MAX_WINNERS`, `MAX_PARTICIPANTS`,
+/// `MIN_WINDOW_LEDGERS`..`MAX_WINDOW_LEDGERS`, `MAX_PROTOCOL_FEE_BPS`).
+///
+/// Cierre programado: cuando vence `registration_deadline` o se llega a
+/// `max_participants`, las inscripciones se rechazan y cualquiera puede
+/// cerrar la sesión con `finalize_close`. Antes de eso el streamer sólo
+/// puede cerrar si `allow_early_close`.
+#[contracttype]
+#[derive(Clone)]
+pub struct SessionConfig {
+    /// Ganadores a extraer (sin reemplazo); el premio se reparte entre ellos
+    pub num_winners: u32,
+    /// Mínimo de participantes para cerrar (≥ 2)
+    pub min_participants: u32,
+    /// Tope de participantes (≥ `num_winners`, ≤ `MAX_PARTICIPANTS`); al alcanzarlo se
+    /// puede cerrar con `finalize_close`
+    pub max_participants: u32,
+    /// Último ledger (secuencia) que admite inscripciones (0 = sin plazo)
+    pub registration_deadline: u32,
+    /// Si el streamer puede cerrar antes del plazo o del tope. Sin plazo
+    /// debe ser true
+    pub allow_early_close: bool,
+    /// Ledgers para revelar tras el cierre (o tras la ventana de nonces)
+    pub reveal_window_ledgers: u32,
+    /// Ledgers para `reveal_nonce` tras el cierre (sólo multi-party)
+    pub nonce_window_ledgers: u32,
+    /// Fianza del streamer (ver `StreamerBond`)
+    pub bond: StreamerBond,
+    /// Entrada de pago (ver `EntryFee`)
+    pub entry_fee: EntryFee,
+    /// Modo multi-party: cada participante aporta un nonce comprometido
+    /// que se combina con el secreto del streamer
+    pub multi_party: bool,
+    /// Raíz Merkle de las direcciones que pueden inscribirse
+    /// (None = inscripción libre). Ver `merkle`
+    pub allowlist_root: Option<BytesN<32>>,
+    /// Requisito de saldo para inscribirse (ver `BalanceGate`)
+    pub balance_gate: BalanceGate,
+    /// Registrar cada ganador como partida en el Game Hub
+    pub report_to_hub: bool,
 }
 
 /// Estado completo de una sesión de sorteo
@@ -87,16 +619,268 @@ pub enum DrawPhase {
 pub struct DrawSession {
     /// Organizador del sorteo
     pub streamer: Address,
-    /// SHA-256(secret_number_be8 || salt32) guardado en commit phase
+    /// Hash-compromiso guardado en commit phase (ver `commitment_scheme`)
     pub commitment: BytesN<32>,
+    /// Función con la que se calculó `commitment`
+    pub commitment_scheme: CommitmentScheme,
     /// Número de participantes registrados
     pub num_participants: u32,
     /// Fase actual del sorteo
     pub phase: DrawPhase,
-    /// Índice ganador (válido solo en fase Revealed)
+    /// Índices ganadores en orden de extracción (vacío hasta el reveal)
+    pub winner_indices: Vec<u32>,
+    /// Contrato del token en el que se paga el premio
+    pub prize_token: Address,
+    /// Premio custodiado por el contrato hasta el reveal
+    pub prize_amount: i128,
+    /// Último ledger (secuencia) en el que se admite el reveal.
+    /// Se fija en `close_registrations` (0 mientras la sesión está abierta)
+    pub reveal_deadline: u32,
+    /// Hash encadenado de la lista: h_i = SHA-256(h_{i-1} || xdr(address_i)),
+    /// con h_0 = 32 bytes a cero. Cada baja encadena además
+    /// SHA-256(h || 0xff || xdr(address)) (ver `unregister_participant`)
+    pub participants_hash: BytesN<32>,
+    /// Entropía fijada en `close_registrations` (cero mientras está abierta):
+    /// SHA-256(prng_32 || ledger_sequence_be4 || participants_hash)
+    pub close_entropy: BytesN<32>,
+    /// Último ledger para `reveal_nonce` (0 fuera del modo multi-party)
+    pub nonce_deadline: u32,
+    /// Participantes que revelaron su nonce
+    pub nonces_revealed: u32,
+    /// XOR de los nonces revelados (orden irrelevante)
+    pub participant_entropy: BytesN<32>,
+    /// Bote de entradas custodiado (`config.entry_fee.amount` × inscritos)
+    pub entry_pool: i128,
+    /// Suma de los pesos de los participantes (boletos). Igual a
+    /// `num_participants` mientras nadie tenga un peso distinto de 1
+    pub total_weight: u32,
+    /// Configuración fijada en `commit_draw` (ver `SessionConfig`)
+    pub config: SessionConfig,
+    /// Estado del informe al Game Hub
+    pub hub_status: HubStatus,
+    /// Llamadas al Hub ya aceptadas: 2k = start_game del ganador k,
+    /// 2k + 1 = su end_game. El reintento continúa desde aquí
+    pub hub_calls_done: u32,
+    /// ID de partida del Hub del ganador 1; el ganador k usa
+    /// `hub_first_id + k - 1`. 0 hasta que el primer informe lo reserva
+    pub hub_first_id: u32,
+    /// Árbol de pesos de la sesión (ver `weights`). Único por sesión
+    /// abierta: un ID reutilizado no ve los pesos de la sesión anterior
+    pub weight_tree: u32,
+    /// Reloj de pausas de reveal (`reveal_pause_clock`) con el que se
+    /// calcularon `nonce_deadline` y `reveal_deadline`
+    pub reveal_clock: u32,
+    /// La fianza se confiscó: la sesión expiró o el streamer la canceló
+    /// tras el cierre (ver `claim_bond_share`)
+    pub bond_slashed: bool,
+    /// Ledger de `commit_draw`. Toda entrada de la sesión se escribió
+    /// después con `TTL_LEDGERS`, así que vive al menos hasta
+    /// `opened_at + TTL_LEDGERS`
+    pub opened_at: u32,
+}
+
+/// Registro compacto y permanente de un sorteo revelado.
+///
+/// Contiene lo necesario para re-derivar los ganadores fuera de la
+/// cadena: commitment, secreto y salt revelados, entropía de la
+/// extracción y hash de la lista de participantes.
+#[contracttype]
+#[derive(Clone)]
+pub struct DrawRecord {
+    /// Organizador del sorteo
+    pub streamer: Address,
+    /// Hash-compromiso publicado en `commit_draw`
+    pub commitment: BytesN<32>,
+    /// Función con la que se calculó `commitment`
+    pub commitment_scheme: CommitmentScheme,
+    /// Secreto revelado (0 si el reveal se hizo con prueba)
+    pub secret_number: u64,
+    /// Salt revelado (None si el reveal se hizo con prueba)
+    pub salt: Option<BytesN<32>>,
+    /// Hash encadenado de la lista de participantes (ver `DrawSession`)
+    pub participants_hash: BytesN<32>,
+    /// Número de participantes registrados
+    pub num_participants: u32,
+    /// Suma de los pesos (la extracción es uniforme si coincide con
+    /// `num_participants`; si no, los pesos salen de los `weight_set`)
+    pub total_weight: u32,
+    /// Entropía usada en la derivación (ver `draw_entropy`)
+    pub draw_entropy: BytesN<32>,
+    /// Índices ganadores en orden de extracción
+    pub winner_indices: Vec<u32>,
+    /// Direcciones ganadoras, en el mismo orden
+    pub winner_addresses: Vec<Address>,
+    /// Token y cantidad del premio pagado
+    pub prize_token: Address,
+    pub prize_amount: i128,
+    /// Ledger (secuencia) del reveal
+    pub revealed_ledger: u32,
+}
+
+// ─── Eventos ─────────────────────────────────────────────────────────────────
+// topic: (nombre, session_id); data con el mismo formato que los indexadores
+// ya consumen (valor único o vector posicional).
+
+/// Fase Commit iniciada. data: commitment
+#[contractevent(topics = ["committed"], data_format = "single-value")]
+pub struct CommittedEvent {
+    #[topic]
+    pub session_id: u32,
+    pub commitment: BytesN<32>,
+}
+
+/// Nuevo participante. data: num_participants
+#[contractevent(topics = ["registered"], data_format = "single-value")]
+pub struct RegisteredEvent {
+    #[topic]
+    pub session_id: u32,
+    pub num_participants: u32,
+}
+
+/// Participante dado de baja; el último pasa a ocupar su índice.
+/// data: (index, moved_from, num_participants)
+#[contractevent(topics = ["unregistered"], data_format = "vec")]
+pub struct UnregisteredEvent {
+    #[topic]
+    pub session_id: u32,
+    pub index: u32,
+    pub moved_from: u32,
+    pub num_participants: u32,
+}
+
+/// Lote inscrito por el streamer. data: (added, already_registered,
+/// num_participants)
+#[contractevent(topics = ["registered_batch"], data_format = "vec")]
+pub struct RegisteredBatchEvent {
+    #[topic]
+    pub session_id: u32,
+    pub added: u32,
+    pub already_registered: u32,
+    pub num_participants: u32,
+}
+
+/// Peso de participante cambiado. data: (index, weight, total_weight)
+#[contractevent(topics = ["weight_set"], data_format = "vec")]
+pub struct WeightSetEvent {
+    #[topic]
+    pub session_id: u32,
+    pub index: u32,
+    pub weight: u32,
+    pub total_weight: u32,
+}
+
+/// Ganador sin el saldo exigido sustituido en el reveal.
+/// data: (drawn_index, winner_index)
+#[contractevent(topics = ["winner_replaced"], data_format = "vec")]
+pub struct WinnerReplacedEvent {
+    #[topic]
+    pub session_id: u32,
+    pub drawn_index: u32,
     pub winner_index: u32,
 }
 
+/// Inscripciones cerradas. data: (num_participants, reveal_deadline)
+#[contractevent(topics = ["closed"], data_format = "vec")]
+pub struct ClosedEvent {
+    #[topic]
+    pub session_id: u32,
+    pub num_participants: u32,
+    pub reveal_deadline: u32,
+}
+
+/// Nonce de participante revelado. data: (index, nonces_revealed)
+#[contractevent(topics = ["nonce_revealed"], data_format = "vec")]
+pub struct NonceRevealedEvent {
+    #[topic]
+    pub session_id: u32,
+    pub index: u32,
+    pub nonces_revealed: u32,
+}
+
+/// Sorteo concluido. data: (winner_indices, winner_addresses,
+/// secret_number, num_participants, prize_token, prize_amount)
+#[contractevent(topics = ["winner"], data_format = "vec")]
+pub struct WinnerEvent {
+    #[topic]
+    pub session_id: u32,
+    pub winner_indices: Vec<u32>,
+    pub winner_addresses: Vec<Address>,
+    pub secret_number: u64,
+    pub num_participants: u32,
+    pub prize_token: Address,
+    pub prize_amount: i128,
+}
+
+/// Informe al Game Hub tras el reveal o un reintento.
+/// data: (status, hub_calls_done)
+#[contractevent(topics = ["hub_report"], data_format = "vec")]
+pub struct HubReportEvent {
+    #[topic]
+    pub session_id: u32,
+    pub status: HubStatus,
+    pub hub_calls_done: u32,
+}
+
+/// Admin propuesto. data: new_admin
+#[contractevent(topics = ["admin_proposed"], data_format = "single-value")]
+pub struct AdminProposedEvent {
+    pub new_admin: Address,
+}
+
+/// Traspaso de admin completado. data: admin
+#[contractevent(topics = ["admin_changed"], data_format = "single-value")]
+pub struct AdminChangedEvent {
+    pub admin: Address,
+}
+
+/// WASM reemplazado. data: new_wasm_hash
+#[contractevent(topics = ["upgraded"], data_format = "single-value")]
+pub struct UpgradedEvent {
+    pub new_wasm_hash: BytesN<32>,
+}
+
+/// Storage migrado. data: (from_version, to_version)
+#[contractevent(topics = ["migrated"], data_format = "vec")]
+pub struct MigratedEvent {
+    pub from_version: u32,
+    pub to_version: u32,
+}
+
+/// Categoría pausada por el admin. data: category
+#[contractevent(topics = ["paused"], data_format = "single-value")]
+pub struct PausedEvent {
+    pub category: PauseCategory,
+}
+
+/// Categoría reanudada por el admin. data: category
+#[contractevent(topics = ["unpaused"], data_format = "single-value")]
+pub struct UnpausedEvent {
+    pub category: PauseCategory,
+}
+
+/// Sesión expirada sin reveal. data: (streamer, num_participants,
+/// reveal_deadline, bond_slashed)
+#[contractevent(topics = ["expired"], data_format = "vec")]
+pub struct ExpiredEvent {
+    #[topic]
+    pub session_id: u32,
+    pub streamer: Address,
+    pub num_participants: u32,
+    pub reveal_deadline: u32,
+    pub bond_slashed: i128,
+}
+
+/// Sorteo cancelado. data: (reason, streamer,
+/// num_participants)
+#[contractevent(topics = ["cancelled"], data_format = "vec")]
+pub struct CancelledEvent {
+    #[topic]
+    pub session_id: u32,
+    pub reason: CancelReason,
+    pub streamer: Address,
+    pub num_participants: u32,
+}
+
 // ─── Contrato Principal ──────────────────────────────────────────────────────
 #[contract]
 pub struct ZkRouletteContract;
@@ -108,19 +892,143 @@ impl ZkRouletteContract {
     //  INICIALIZACIÓN
     // ════════════════════════════════════════════════════════════════════════
 
-    /// Inicializa el contrato guardando la dirección del Game Hub.
+    /// Inicializa el contrato guardando el admin y la dirección del Game Hub.
     /// Debe llamarse una sola vez tras el deploy.
     ///
     /// # Arguments
+    /// * `admin`    — Cuenta que autoriza upgrades y migraciones (firma requerida)
     /// * `game_hub` — Dirección del contrato Game Hub del hackathon
-    pub fn initialize(env: Env, game_hub: Address) {
+    pub fn initialize(env: Env, admin: Address, game_hub: Address) -> Result<(), RouletteError> {
         // Prevenir re-inicialización
         if env.storage().instance().has(&DataKey::GameHub) {
-            panic!("already initialized");
+            return Err(RouletteError::AlreadyInitialized);
         }
+        admin.require_auth();
+        env.storage().instance().set(&DataKey::Admin, &admin);
         env.storage().instance().set(&DataKey::GameHub, &game_hub);
+        env.storage().instance().set(&DataKey::Version, &CONTRACT_VERSION);
         // El storage de instancia vive mientras el contrato exista
         env.storage().instance().extend_ttl(TTL_LEDGERS, TTL_LEDGERS);
+
+        Ok(())
+    }
+
+    /// Carga la clave de verificación del circuito Noir. Se fija una sola
+    /// vez, igual que el Game Hub, justo después de `initialize`.
+    /// Sólo el admin.
+    ///
+    /// # Arguments
+    /// * `vk` — Clave Groth16 con `NUM_PUBLIC_INPUTS + 1` puntos IC
+    pub fn set_verification_key(env: Env, vk: VerificationKey) -> Result<(), RouletteError> {
+        require_admin(&env)?;
+        if env.storage().instance().has(&DataKey::VerificationKey) {
+            return Err(RouletteError::VerificationKeyAlreadySet);
+        }
+        if vk.ic.len() != verifier::NUM_PUBLIC_INPUTS + 1 {
+            return Err(RouletteError::InvalidVerificationKey);
+        }
+        env.storage().instance().set(&DataKey::VerificationKey, &vk);
+
+        Ok(())
+    }
+
+    // ════════════════════════════════════════════════════════════════════════
+    //  ADMINISTRACIÓN
+    // ════════════════════════════════════════════════════════════════════════
+
+    /// Primer paso del traspaso de admin: el admin actual propone a
+    /// `new_admin`, que debe aceptar con `accept_admin`. Una propuesta
+    /// nueva reemplaza a la anterior.
+    pub fn propose_admin(env: Env, new_admin: Address) -> Result<(), RouletteError> {
+        require_admin(&env)?;
+        env.storage().instance().set(&DataKey::PendingAdmin, &new_admin);
+
+        AdminProposedEvent { new_admin }.publish(&env);
+
+        Ok(())
+    }
+
+    /// Segundo paso del traspaso: el admin propuesto firma y toma el rol.
+    /// Así un error al escribir la dirección no deja el contrato sin admin.
+    pub fn accept_admin(env: Env) -> Result<(), RouletteError> {
+        let pending: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::PendingAdmin)
+            .ok_or(RouletteError::NoPendingAdmin)?;
+        pending.require_auth();
+
+        env.storage().instance().set(&DataKey::Admin, &pending);
+        env.storage().instance().remove(&DataKey::PendingAdmin);
+
+        AdminChangedEvent { admin: pending }.publish(&env);
+
+        Ok(())
+    }
+
+    /// Fija el destino de la comisión del protocolo sobre las entradas
+    /// (por defecto, el admin). Sólo el admin.
+    pub fn set_fee_recipient(env: Env, recipient: Address) -> Result<(), RouletteError> {
+        require_admin(&env)?;
+        env.storage().instance().set(&DataKey::FeeRecipient, &recipient);
+
+        Ok(())
+    }
+
+    /// Pausa una categoría de operaciones (interruptor de emergencia).
+    /// Pausar lo ya pausado no hace nada. Sólo el admin.
+    pub fn pause(env: Env, category: PauseCategory) -> Result<(), RouletteError> {
+        require_admin(&env)?;
+        set_paused(&env, category, true);
+
+        Ok(())
+    }
+
+    /// Reanuda una categoría pausada. Sólo el admin.
+    pub fn unpause(env: Env, category: PauseCategory) -> Result<(), RouletteError> {
+        require_admin(&env)?;
+        set_paused(&env, category, false);
+
+        Ok(())
+    }
+
+    /// Reemplaza el WASM del contrato conservando su ID y su storage.
+    /// El nuevo código se ejecuta a partir de la siguiente invocación;
+    /// si cambia el layout de storage hay que llamar después a `migrate`.
+    ///
+    /// # Arguments
+    /// * `new_wasm_hash` — Hash del WASM ya subido a la red
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), RouletteError> {
+        require_admin(&env)?;
+        env.deployer()
+            .update_current_contract_wasm(new_wasm_hash.clone());
+
+        UpgradedEvent { new_wasm_hash }.publish(&env);
+
+        Ok(())
+    }
+
+    /// Lleva el storage desde la versión guardada hasta `CONTRACT_VERSION`,
+    /// un paso de `migrate_step` por versión. Las sesiones temporales no se
+    /// recorren: `load_session` convierte cada `StoredSession` al leerla.
+    pub fn migrate(env: Env) -> Result<(), RouletteError> {
+        require_admin(&env)?;
+        let from_version = storage_version(&env);
+        if from_version >= CONTRACT_VERSION {
+            return Err(RouletteError::AlreadyMigrated);
+        }
+
+        let mut version = from_version;
+        while version < CONTRACT_VERSION {
+            migrate_step(&env, version)?;
+            version += 1;
+        }
+        env.storage().instance().set(&DataKey::Version, &CONTRACT_VERSION);
+        env.storage().instance().extend_ttl(TTL_LEDGERS, TTL_LEDGERS);
+
+        MigratedEvent { from_version, to_version: CONTRACT_VERSION }.publish(&env);
+
+        Ok(())
     }
 
     // ════════════════════════════════════════════════════════════════════════
@@ -129,52 +1037,87 @@ impl ZkRouletteContract {
 
     /// El streamer publica su compromiso hash ANTES de conocer los participantes.
     ///
-    /// El `commitment` debe ser:
-    ///   SHA-256( secret_number_as_8_bytes_big_endian || salt_32_bytes )
+    /// El `commitment` debe ser, según `commitment_scheme`:
+    ///   Sha256   → SHA-256( secret_number_as_8_bytes_big_endian || salt_32_bytes )
+    ///   Poseidon → Poseidon( secret_number, salt ) sobre BN254, big-endian;
+    ///              es el valor que prueba el circuito Noir y el único que
+    ///              admite `reveal_winner_with_proof`
     ///
     /// Este valor se calcula en el frontend (JS) y no puede ser alterado
     /// después de esta transacción.
     ///
+    /// El premio se transfiere del streamer al contrato en esta misma
+    /// transacción (interfaz token de Soroban) y queda custodiado hasta
+    /// `reveal_winner`, que lo paga al ganador de forma atómica. La fianza
+    /// también queda custodiada: es lo que pierde el streamer si no revela.
+    ///
     /// # Arguments
-    /// * `streamer`    — Cuenta del organizador (firma requerida)
-    /// * `session_id`  — ID único del sorteo (elegido por el streamer)
-    /// * `commitment`  — SHA-256(secret || salt)
+    /// * `streamer`     — Cuenta del organizador (firma requerida)
+    /// * `session_id`   — ID único del sorteo, menor que
+    ///                    `AUTO_SESSION_ID_START` (elegido por el streamer;
+    ///                    ver `create_draw` para que lo asigne el contrato)
+    /// * `commitment`   — Hash(secret || salt)
+    /// * `commitment_scheme` — Función hash del commitment
+    /// * `prize_token`  — Contrato del token del premio (p.ej. SAC de XLM)
+    /// * `prize_amount` — Cantidad a custodiar (0 = sorteo sin premio)
+    /// * `config`       — Ganadores, límites, plazos, fianza, entrada,
+    ///                    elegibilidad y Game Hub (ver `SessionConfig`)
     pub fn commit_draw(
         env: Env,
         streamer: Address,
         session_id: u32,
         commitment: BytesN<32>,
-    ) {
-        // Solo el streamer puede hacer commit
-        streamer.require_auth();
-
-        // Una sesión con ese ID no debe existir
-        if env.storage().temporary().has(&DataKey::Session(session_id)) {
-            panic!("session already exists");
+        commitment_scheme: CommitmentScheme,
+        prize_token: Address,
+        prize_amount: i128,
+        config: SessionConfig,
+    ) -> Result<(), RouletteError> {
+        if session_id >= AUTO_SESSION_ID_START {
+            return Err(RouletteError::SessionIdReserved);
         }
-
-        let session = DrawSession {
+        open_session(
+            &env,
             streamer,
+            session_id,
             commitment,
-            num_participants: 0,
-            phase: DrawPhase::Open,
-            winner_index: 0,
-        };
-
-        // Guardar sesión y lista vacía de participantes
-        env.storage().temporary().set(&DataKey::Session(session_id), &session);
-        let empty_participants: Vec<Address> = Vec::new(&env);
-        env.storage().temporary().set(&DataKey::Participants(session_id), &empty_participants);
-
-        // Establecer TTL de 30 días
-        env.storage().temporary().extend_ttl(&DataKey::Session(session_id), TTL_LEDGERS, TTL_LEDGERS);
-        env.storage().temporary().extend_ttl(&DataKey::Participants(session_id), TTL_LEDGERS, TTL_LEDGERS);
+            commitment_scheme,
+            prize_token,
+            prize_amount,
+            config,
+        )
+    }
 
-        // Evento: fase Commit iniciada
-        env.events().publish(
-            (Symbol::new(&env, "committed"), session_id),
+    /// Igual que `commit_draw`, pero el contrato asigna el ID de sesión y
+    /// lo retorna. Evita que un tercero ocupe el ID antes que el streamer
+    /// (front-running) y las colisiones de IDs derivados del reloj.
+    ///
+    /// Los IDs salen de un contador que empieza en `AUTO_SESSION_ID_START`,
+    /// un rango que `commit_draw` no acepta: nunca hay que saltar IDs
+    /// ocupados. El rango acaba en `HUB_SESSION_ID_START`.
+    ///
+    /// # Returns
+    /// El `session_id` asignado
+    pub fn create_draw(
+        env: Env,
+        streamer: Address,
+        commitment: BytesN<32>,
+        commitment_scheme: CommitmentScheme,
+        prize_token: Address,
+        prize_amount: i128,
+        config: SessionConfig,
+    ) -> Result<u32, RouletteError> {
+        let session_id = allocate_session_id(&env)?;
+        open_session(
+            &env,
+            streamer,
+            session_id,
             commitment,
-        );
+            commitment_scheme,
+            prize_token,
+            prize_amount,
+            config,
+        )?;
+        Ok(session_id)
     }
 
     // ════════════════════════════════════════════════════════════════════════
@@ -183,382 +1126,3924 @@ impl ZkRouletteContract {
 
     /// Un espectador se registra como participante del sorteo.
     ///
+    /// En modo multi-party debe aportar
+    ///   nonce_commitment = SHA-256( nonce_32 || xdr(participant) )
+    /// La dirección entra en el hash para que nadie pueda copiar el
+    /// compromiso de otro participante.
+    ///
+    /// Si la sesión tiene allowlist, `allowlist_proof` son los hermanos
+    /// del camino de la hoja de `participant` a la raíz (ver `merkle::proof`).
+    ///
     /// # Arguments
-    /// * `session_id`   — ID del sorteo activo
-    /// * `participant`  — Cuenta que se registra (firma requerida)
+    /// * `session_id`       — ID del sorteo activo
+    /// * `participant`      — Cuenta que se registra (firma requerida)
+    /// * `nonce_commitment` — Obligatorio en modo multi-party; None si no
+    /// * `allowlist_proof`  — Obligatoria con allowlist; None si no
     pub fn register_participant(
         env: Env,
         session_id: u32,
         participant: Address,
-    ) {
+        nonce_commitment: Option<BytesN<32>>,
+        allowlist_proof: Option<Vec<BytesN<32>>>,
+    ) -> Result<(), RouletteError> {
         participant.require_auth();
+        require_not_paused(&env, PauseCategory::Registration)?;
 
-        let mut session: DrawSession = env
-            .storage()
-            .temporary()
-            .get(&DataKey::Session(session_id))
-            .unwrap_or_else(|| panic!("session not found"));
+        let mut session = load_session(&env, session_id)?;
 
-        if session.phase != DrawPhase::Open {
-            panic!("registration is closed");
+        if !registration_open(&env, &session) {
+            return Err(RouletteError::RegistrationClosed);
         }
-        if session.num_participants >= MAX_PARTICIPANTS {
-            panic!("max participants reached");
+        if session.num_participants >= session.config.max_participants {
+            return Err(RouletteError::MaxParticipantsReached);
         }
 
-        let mut participants: Vec<Address> = env
-            .storage()
-            .temporary()
-            .get(&DataKey::Participants(session_id))
-            .unwrap_or_else(|| panic!("participants not found"));
+        // Evitar duplicados: una clave por (sesión, dirección)
+        let index_key = DataKey::ParticipantIndex(session_id, participant.clone());
+        if env.storage().temporary().has(&index_key) {
+            return Err(RouletteError::AlreadyRegistered);
+        }
 
-        // Evitar duplicados
-        for i in 0..participants.len() {
-            if participants.get(i).unwrap() == participant {
-                panic!("already registered");
+        match (&session.config.allowlist_root, allowlist_proof) {
+            (Some(root), Some(proof)) => {
+                if !merkle::verify(&env, root, &participant, &proof) {
+                    return Err(RouletteError::NotOnAllowlist);
+                }
             }
+            (Some(_), None) => return Err(RouletteError::AllowlistProofRequired),
+            (None, Some(_)) => return Err(RouletteError::AllowlistProofNotAccepted),
+            (None, None) => {}
+        }
+
+        if !holds_min_balance(&env, &session.config.balance_gate, &participant) {
+            return Err(RouletteError::InsufficientTokenBalance);
         }
 
-        participants.push_back(participant);
-        session.num_participants = participants.len();
+        let index = session.num_participants;
+        match (session.config.multi_party, nonce_commitment) {
+            (true, Some(nonce_commitment)) => {
+                set_temporary(&env, &DataKey::NonceCommitment(session_id, index), &nonce_commitment);
+            }
+            (true, None) => return Err(RouletteError::NonceCommitmentRequired),
+            (false, Some(_)) => return Err(RouletteError::NonceCommitmentNotAccepted),
+            (false, None) => {}
+        }
 
-        env.storage().temporary().set(&DataKey::Session(session_id), &session);
-        env.storage().temporary().set(&DataKey::Participants(session_id), &participants);
+        charge_entry_fee(&env, &mut session, &participant);
+        append_participant(&env, session_id, &mut session, &participant);
+        save_session(&env, session_id, &session);
 
         // Refrescar TTL al agregar participantes
         env.storage().temporary().extend_ttl(&DataKey::Session(session_id), TTL_LEDGERS, TTL_LEDGERS);
-        env.storage().temporary().extend_ttl(&DataKey::Participants(session_id), TTL_LEDGERS, TTL_LEDGERS);
 
-        env.events().publish(
-            (Symbol::new(&env, "registered"), session_id),
-            session.num_participants,
-        );
-    }
+        RegisteredEvent { session_id, num_participants: session.num_participants }.publish(&env);
 
-    // ════════════════════════════════════════════════════════════════════════
-    //  CIERRE DE INSCRIPCIONES
-    // ════════════════════════════════════════════════════════════════════════
+        Ok(())
+    }
 
-    /// El streamer cierra las inscripciones y congela la lista de participantes.
-    /// Tras esto, `num_participants` queda fijo para el cálculo del ganador.
+    /// El streamer inscribe un lote de participantes en una transacción.
+    ///
+    /// Las direcciones ya inscritas (o repetidas dentro del lote) se omiten
+    /// sin error y se cuentan en el evento. Si el lote no cabe completo
+    /// bajo el tope de participantes, o alguna dirección no tiene el saldo
+    /// mínimo de `BalanceGate`, se rechaza entero. No admite sesiones
+    /// multi-party ni con allowlist: cada participante debe aportar su
+    /// compromiso de nonce o su prueba Merkle con `register_participant`.
+    /// Con entrada de pago cada participante paga la suya, así que
+    /// `require_participant_auth` debe ser true.
     ///
     /// # Arguments
-    /// * `session_id` — ID del sorteo
-    pub fn close_registrations(env: Env, session_id: u32) {
-        let mut session: DrawSession = env
-            .storage()
-            .temporary()
-            .get(&DataKey::Session(session_id))
-            .unwrap_or_else(|| panic!("session not found"));
+    /// * `session_id`   — ID del sorteo activo
+    /// * `participants` — Hasta `MAX_BATCH` direcciones, en orden de inscripción
+    /// * `require_participant_auth` — Exige además la firma de cada
+    ///                    participante (inscripción consentida)
+    ///
+    /// # Returns
+    /// Número de participantes añadidos
+    pub fn register_participants(
+        env: Env,
+        session_id: u32,
+        participants: Vec<Address>,
+        require_participant_auth: bool,
+    ) -> Result<u32, RouletteError> {
+        let mut session = load_session(&env, session_id)?;
 
         session.streamer.require_auth();
+        require_not_paused(&env, PauseCategory::Registration)?;
 
-        if session.phase != DrawPhase::Open {
-            panic!("session is not open");
+        if !registration_open(&env, &session) {
+            return Err(RouletteError::RegistrationClosed);
+        }
+        if session.config.multi_party {
+            return Err(RouletteError::NonceCommitmentRequired);
+        }
+        if session.config.allowlist_root.is_some() {
+            return Err(RouletteError::AllowlistProofRequired);
+        }
+        if session.config.entry_fee.amount > 0 && !require_participant_auth {
+            return Err(RouletteError::ParticipantAuthRequired);
         }
-        if session.num_participants < 2 {
-            panic!("need at least 2 participants");
+        if participants.is_empty() || participants.len() > MAX_BATCH {
+            return Err(RouletteError::InvalidBatchSize);
         }
 
-        session.phase = DrawPhase::Closed;
-        env.storage().temporary().set(&DataKey::Session(session_id), &session);
+        let mut added: u32 = 0;
+        let mut already_registered: u32 = 0;
+        for participant in participants.iter() {
+            if require_participant_auth {
+                participant.require_auth();
+            }
+            if env
+                .storage()
+                .temporary()
+                .has(&DataKey::ParticipantIndex(session_id, participant.clone()))
+            {
+                already_registered += 1;
+                continue;
+            }
+            if session.num_participants >= session.config.max_participants {
+                return Err(RouletteError::MaxParticipantsReached);
+            }
+            if !holds_min_balance(&env, &session.config.balance_gate, &participant) {
+                return Err(RouletteError::InsufficientTokenBalance);
+            }
+            charge_entry_fee(&env, &mut session, &participant);
+            append_participant(&env, session_id, &mut session, &participant);
+            added += 1;
+        }
 
-        env.events().publish(
-            (Symbol::new(&env, "closed"), session_id),
-            session.num_participants,
-        );
-    }
+        save_session(&env, session_id, &session);
+        env.storage().temporary().extend_ttl(&DataKey::Session(session_id), TTL_LEDGERS, TTL_LEDGERS);
 
-    // ════════════════════════════════════════════════════════════════════════
-    //  FASE 3 — REVEAL
-    // ════════════════════════════════════════════════════════════════════════
+        RegisteredBatchEvent {
+            session_id,
+            added,
+            already_registered,
+            num_participants: session.num_participants,
+        }
+        .publish(&env);
 
-    /// El streamer revela su secreto. El contrato:
-    ///   1. Verifica que SHA-256(secret || salt) == commitment almacenado
-    ///   2. Calcula winner_index = secret_number % num_participants
-    ///   3. Llama a start_game() en el Game Hub
-    ///   4. Llama a end_game() declarando al ganador
-    ///   5. Emite evento con la address ganadora
+        Ok(added)
+    }
+
+    /// Un participante se da de baja mientras la sesión está abierta y
+    /// recupera su entrada, si la pagó.
     ///
-    /// # Arguments
-    /// * `session_id`     — ID del sorteo
-    /// * `secret_number`  — El número secreto original (privado hasta ahora)
-    /// * `salt`           — El salt original (32 bytes)
+    /// El último participante pasa a ocupar el índice liberado (con su
+    /// compromiso de nonce y su peso), así que los índices siguen siendo
+    /// `0..num_participants` sin huecos. La baja se encadena en
+    /// `participants_hash`.
     ///
-    /// El frontend primero genera la prueba Noir off-chain para auditoría
-    /// pública; la verificación on-chain usa este reveal directo.
-    pub fn reveal_winner(
-        env: Env,
-        session_id: u32,
-        secret_number: u64,
-        salt: BytesN<32>,
-    ) {
-        let mut session: DrawSession = env
+    /// # Arguments
+    /// * `session_id`  — ID del sorteo abierto
+    /// * `participant` — Cuenta inscrita (firma requerida)
+    pub fn unregister_participant(env: Env, session_id: u32, participant: Address) -> Result<(), RouletteError> {
+        participant.require_auth();
+
+        let mut session = load_session(&env, session_id)?;
+
+        if !registration_open(&env, &session) {
+            return Err(RouletteError::RegistrationClosed);
+        }
+        let index: u32 = env
             .storage()
             .temporary()
-            .get(&DataKey::Session(session_id))
-            .unwrap_or_else(|| panic!("session not found"));
+            .get(&DataKey::ParticipantIndex(session_id, participant.clone()))
+            .ok_or(RouletteError::NotRegistered)?;
 
-        // Solo el streamer puede revelar
-        session.streamer.require_auth();
+        let moved_from = remove_participant(&env, session_id, &mut session, index, &participant)?;
 
-        if session.phase != DrawPhase::Closed {
-            panic!("must close registrations before reveal");
-        }
-        if secret_number == 0 {
-            panic!("secret_number must be > 0");
+        let fee = &session.config.entry_fee;
+        if fee.amount > 0 {
+            token::Client::new(&env, &fee.token).transfer(&env.current_contract_address(), &participant, &fee.amount);
+            session.entry_pool -= fee.amount;
         }
+        save_session(&env, session_id, &session);
 
-        // ── 1. Verificación del Commitment on-chain ────────────────────────
-        //
-        //  Reconstruye SHA-256(secret_number_be8 || salt_32) y compara
-        //  con el commitment guardado en la fase Commit.
-        //
-        //  Nota de implementación:
-        //    En Protocol 25, env.crypto().sha256() usa SHA-256 estándar.
-        //    El frontend calcula el mismo hash en JS usando SubtleCrypto.
-        //    El circuito Noir usa Poseidon para la prueba formal off-chain;
-        //    para la verificación on-chain usamos SHA-256 por compatibilidad
-        //    directa con la API Soroban.
+        UnregisteredEvent {
+            session_id,
+            index,
+            moved_from,
+            num_participants: session.num_participants,
+        }
+        .publish(&env);
 
-        let mut preimage = Bytes::new(&env);
+        Ok(())
+    }
 
-        // Serializar secret_number como 8 bytes big-endian
-        let secret_bytes = secret_number.to_be_bytes();
-        for b in secret_bytes.iter() {
+    /// El streamer asigna un peso (número de boletos) a un participante
+    /// antes del cierre: su probabilidad de ganar pasa a ser
+    /// proporcional a `weight`. Todos empiezan con peso 1.
+    ///
+    /// La extracción con pesos no recorre la lista (ver `weights`). Con
+    /// `reveal_winner_with_proof`, la entrada pública de participantes
+    /// es el peso total. No disponible en modo multi-party.
+    ///
+    /// # Arguments
+    /// * `session_id`  — ID del sorteo abierto
+    /// * `participant` — Cuenta inscrita
+    /// * `weight`      — Nuevo peso, en `1..=MAX_WEIGHT`
+    pub fn set_participant_weight(
+        env: Env,
+        session_id: u32,
+        participant: Address,
+        weight: u32,
+    ) -> Result<(), RouletteError> {
+        let mut session = load_session(&env, session_id)?;
+
+        session.streamer.require_auth();
+
+        if session.config.multi_party {
+            return Err(RouletteError::WeightsNotSupported);
+        }
+        if !registration_open(&env, &session) {
+            return Err(RouletteError::RegistrationClosed);
+        }
+        if weight == 0 || weight > weights::MAX_WEIGHT {
+            return Err(RouletteError::InvalidWeight);
+        }
+        let index: u32 = env
+            .storage()
+            .temporary()
+            .get(&DataKey::ParticipantIndex(session_id, participant))
+            .ok_or(RouletteError::NotRegistered)?;
+
+        let previous = weights::set_weight(&env, session.weight_tree, session.config.max_participants, index, weight);
+        session.total_weight = session.total_weight - previous + weight;
+        save_session(&env, session_id, &session);
+
+        WeightSetEvent { session_id, index, weight, total_weight: session.total_weight }.publish(&env);
+
+        Ok(())
+    }
+
+    // ════════════════════════════════════════════════════════════════════════
+    //  CIERRE DE INSCRIPCIONES
+    // ════════════════════════════════════════════════════════════════════════
+
+    /// El streamer cierra las inscripciones y congela la lista de participantes.
+    /// Tras esto, `num_participants` queda fijo para el cálculo del ganador
+    /// y empieza a correr el plazo de reveal (`reveal_window_ledgers`).
+    ///
+    /// Aquí se fija `close_entropy`, que entra en la derivación del ganador.
+    /// Combina la salida del PRNG del ledger, la secuencia de cierre y el
+    /// hash de la lista congelada: nada de eso era conocido por el streamer
+    /// cuando se comprometió con su secreto.
+    ///
+    /// En modo multi-party abre además la ventana de `reveal_nonce`; el
+    /// plazo del streamer empieza a contar cuando esa ventana termina.
+    ///
+    /// Sin `allow_early_close` en la configuración, el streamer no puede
+    /// cerrar antes del plazo o del tope (ver `finalize_close`).
+    ///
+    /// # Arguments
+    /// * `session_id` — ID del sorteo
+    pub fn close_registrations(env: Env, session_id: u32) -> Result<(), RouletteError> {
+        let session = load_session(&env, session_id)?;
+
+        session.streamer.require_auth();
+
+        if session.phase != DrawPhase::Open {
+            return Err(RouletteError::SessionNotOpen);
+        }
+        if !session.config.allow_early_close && !close_due(&env, &session) {
+            return Err(RouletteError::EarlyCloseNotAllowed);
+        }
+
+        close_session(&env, session_id, session)
+    }
+
+    /// Cierra una sesión una vez vencido su plazo de inscripción o
+    /// alcanzado su tope de participantes. Puede llamarla
+    /// cualquiera (no requiere firma); el cierre es el mismo que el de
+    /// `close_registrations`.
+    ///
+    /// Si el plazo vence con menos de `min_participants` (o de
+    /// `num_winners`), la sesión se cancela en su lugar
+    /// (`CancelReason::Undersubscribed`): premio y fianza vuelven al
+    /// streamer y las entradas se reembolsan con `claim_ticket_refund`.
+    ///
+    /// # Arguments
+    /// * `session_id` — ID del sorteo
+    pub fn finalize_close(env: Env, session_id: u32) -> Result<(), RouletteError> {
+        let session = load_session(&env, session_id)?;
+
+        if session.phase != DrawPhase::Open {
+            return Err(RouletteError::SessionNotOpen);
+        }
+        if !close_due(&env, &session) {
+            return Err(RouletteError::CloseNotDue);
+        }
+        if registration_deadline_passed(&env, &session)
+            && (session.num_participants < session.config.min_participants
+                || session.num_participants < session.config.num_winners)
+        {
+            cancel_session(&env, session_id, session, CancelReason::Undersubscribed);
+            return Ok(());
+        }
+
+        close_session(&env, session_id, session)
+    }
+
+    // ════════════════════════════════════════════════════════════════════════
+    //  FASE 2b — REVEAL DE NONCES (modo multi-party)
+    // ════════════════════════════════════════════════════════════════════════
+
+    /// Un participante revela el nonce que comprometió al registrarse.
+    ///
+    /// Los nonces revelados se combinan por XOR en `participant_entropy`
+    /// (el orden de reveal no afecta a la entropía). Quien no revele antes
+    /// de `nonce_deadline` queda excluido del sorteo; los que revelan
+    /// forman el pool de elegibles en orden de reveal (ver `NonceRevealedEvent`).
+    ///
+    /// # Arguments
+    /// * `session_id`  — ID del sorteo
+    /// * `participant` — Cuenta registrada (firma requerida)
+    /// * `nonce`       — Nonce de 32 bytes comprometido en el registro
+    pub fn reveal_nonce(env: Env, session_id: u32, participant: Address, nonce: BytesN<32>) -> Result<(), RouletteError> {
+        participant.require_auth();
+
+        let mut session = load_session(&env, session_id)?;
+
+        if !session.config.multi_party {
+            return Err(RouletteError::NotMultiParty);
+        }
+        if session.phase != DrawPhase::Closed {
+            return Err(RouletteError::SessionNotClosed);
+        }
+        if env.ledger().sequence() > session.nonce_deadline {
+            return Err(RouletteError::NonceWindowEnded);
+        }
+
+        let index: u32 = env
+            .storage()
+            .temporary()
+            .get(&DataKey::ParticipantIndex(session_id, participant.clone()))
+            .ok_or(RouletteError::NotRegistered)?;
+
+        if env.storage().temporary().has(&DataKey::NonceRevealed(session_id, index)) {
+            return Err(RouletteError::NonceAlreadyRevealed);
+        }
+
+        let commitment: BytesN<32> = env
+            .storage()
+            .temporary()
+            .get(&DataKey::NonceCommitment(session_id, index))
+            .ok_or(RouletteError::NonceCommitmentNotFound)?;
+        let mut preimage = Bytes::from_array(&env, &nonce.to_array());
+        preimage.append(&participant.to_xdr(&env));
+        let computed: BytesN<32> = env.crypto().sha256(&preimage).into();
+        if computed != commitment {
+            return Err(RouletteError::NonceCommitmentMismatch);
+        }
+
+        let mut acc = session.participant_entropy.to_array();
+        for (a, b) in acc.iter_mut().zip(nonce.to_array().iter()) {
+            *a ^= *b;
+        }
+        session.participant_entropy = BytesN::from_array(&env, &acc);
+
+        // Posición en el pool de elegibles = orden de reveal
+        set_temporary(&env, &DataKey::NonceRevealed(session_id, index), &true);
+        set_temporary(&env, &DataKey::EligibleIndex(session_id, session.nonces_revealed), &index);
+        session.nonces_revealed += 1;
+
+        save_session(&env, session_id, &session);
+
+        NonceRevealedEvent { session_id, index, nonces_revealed: session.nonces_revealed }
+            .publish(&env);
+
+        Ok(())
+    }
+
+    // ════════════════════════════════════════════════════════════════════════
+    //  FASE 3 — REVEAL
+    // ════════════════════════════════════════════════════════════════════════
+
+    /// El streamer revela su secreto. El contrato:
+    ///   1. Verifica que Hash(secret || salt) == commitment almacenado
+    ///      (SHA-256 o Poseidon, según `commitment_scheme`)
+    ///   2. Deriva `num_winners` índices distintos a partir de
+    ///      (secret, salt), uniformes o proporcionales al peso de cada
+    ///      participante — ver `selection`
+    ///   3. Reparte el premio custodiado entre los ganadores
+    ///   4. Devuelve la fianza al streamer
+    ///   5. Guarda el resultado y emite evento con las addresses
+    ///      ganadoras y el premio
+    ///   6. Llama a start_game() y end_game() en el Game Hub por cada
+    ///      ganador. Un fallo del Hub no revierte el reveal: queda
+    ///      `HubStatus::Pending` y se reintenta con `retry_hub_report`
+    ///
+    /// # Arguments
+    /// * `session_id`     — ID del sorteo
+    /// * `secret_number`  — El número secreto original (privado hasta ahora)
+    /// * `salt`           — El salt original (32 bytes)
+    ///
+    /// Para revelar sin publicar el secreto, ver `reveal_winner_with_proof`.
+    pub fn reveal_winner(
+        env: Env,
+        session_id: u32,
+        secret_number: u64,
+        salt: BytesN<32>,
+    ) -> Result<(), RouletteError> {
+        let session = load_session(&env, session_id)?;
+
+        // Solo el streamer puede revelar
+        session.streamer.require_auth();
+        require_not_paused(&env, PauseCategory::Reveal)?;
+
+        if session.phase != DrawPhase::Closed {
+            return Err(RouletteError::SessionNotClosed);
+        }
+        if env.ledger().sequence() > session.reveal_deadline {
+            return Err(RouletteError::RevealDeadlinePassed);
+        }
+        if session.config.multi_party
+            && session.nonces_revealed < session.num_participants
+            && env.ledger().sequence() <= session.nonce_deadline
+        {
+            return Err(RouletteError::NonceWindowOpen);
+        }
+        if lacks_nonces(&session) {
+            return Err(RouletteError::NotEnoughNonces);
+        }
+        if secret_number == 0 {
+            return Err(RouletteError::InvalidSecret);
+        }
+
+        // ── 1. Verificación del Commitment on-chain ────────────────────────
+        //
+        //  Reconstruye el hash con la función elegida en commit_draw y
+        //  compara con el commitment guardado en la fase Commit.
+        //
+        //  Nota de implementación:
+        //    Sha256   → env.crypto().sha256(), igual que SubtleCrypto en JS.
+        //    Poseidon → host function de Protocol 25 con los parámetros de
+        //               circomlib/Noir (ver `poseidon`): el mismo valor que
+        //               prueba el circuito. El salt debe ser < r.
+
+        let computed_hash: BytesN<32> = match session.commitment_scheme {
+            CommitmentScheme::Sha256 => {
+                // secret_number como 8 bytes big-endian + 32 bytes del salt
+                let mut preimage = Bytes::from_array(&env, &secret_number.to_be_bytes());
+                preimage.extend_from_array(&salt.to_array());
+                env.crypto().sha256(&preimage).into()
+            }
+            CommitmentScheme::Poseidon => {
+                if !poseidon::is_field_element(&salt) {
+                    return Err(RouletteError::SaltNotInField);
+                }
+                poseidon::commitment(&env, secret_number, &salt)
+            }
+        };
+
+        if computed_hash != session.commitment {
+            return Err(RouletteError::CommitmentMismatch);
+        }
+
+        // ── 2. Cálculo de los Ganadores ───────────────────────────────────
+        //
+        //  Muestreo por rechazo sobre
+        //  SHA-256(secret || salt || entropy || contador): sin el sesgo
+        //  de `secret % n` y ligado a la entropía fijada al cerrar. Misma
+        //  derivación que el circuito Noir (primer ganador) y que
+        //  `deriveWinners` en el frontend.
+        //
+        //  En modo multi-party la derivación se hace sobre la lista de
+        //  participantes elegibles (ver `eligible_pool_size`) y
+        //  entropy = SHA-256(close_entropy || participant_entropy).
+
+        //
+        //  Con pesos la extracción es sobre boletos: `weights::locate`
+        //  encuentra al dueño de cada boleto en O(log n).
+
+        let entropy = draw_entropy(&env, &session);
+        let winner_indices = if is_weighted(&session) {
+            selection::derive_weighted_winners(
+                &env,
+                secret_number,
+                &salt,
+                &entropy,
+                session.total_weight as u64,
+                session.config.num_winners,
+                |ticket, taken| weights::locate(&env, session.weight_tree, session.num_participants, ticket, taken),
+            )
+        } else {
+            let drawn = selection::derive_winners(
+                &env,
+                secret_number,
+                &salt,
+                &entropy,
+                eligible_pool_size(&session),
+                session.config.num_winners,
+            );
+            let mut winner_indices: Vec<u32> = Vec::new(&env);
+            for position in drawn.iter() {
+                winner_indices.push_back(eligible_index(&env, session_id, &session, position)?);
+            }
+            winner_indices
+        };
+
+        let winner_indices = recheck_balance_gate(&env, session_id, &session, winner_indices);
+        settle_reveal(&env, session_id, session, winner_indices, secret_number, Some(salt))
+    }
+
+    /// Reveal con prueba ZK: el streamer no publica su secreto. El
+    /// contrato verifica la prueba Groth16 de circuits/src/main.nr y usa
+    /// la salida pública `winner_index` del circuito.
+    ///
+    /// Para usar esta vía, la sesión debe haberse creado con
+    /// `CommitmentScheme::Poseidon`.
+    /// Sólo admite un ganador (el circuito extrae uno).
+    ///
+    /// Las entradas públicas se comprueban contra el estado on-chain:
+    ///   · [0]      == commitment almacenado
+    ///   · [1]      == participantes que entran en la extracción (boletos
+    ///                 con pesos: el índice extraído es un boleto)
+    ///   · [2..34]  == bytes de la entropía del sorteo (`draw_entropy`)
+    ///   · [34]     == winner_index, que debe estar en rango
+    ///
+    /// # Arguments
+    /// * `session_id`    — ID del sorteo
+    /// * `proof`         — Prueba Groth16 (A, B, C)
+    /// * `public_inputs` — Entradas públicas en el orden del ABI de Noir
+    pub fn reveal_winner_with_proof(
+        env: Env,
+        session_id: u32,
+        proof: Groth16Proof,
+        public_inputs: Vec<BytesN<32>>,
+    ) -> Result<(), RouletteError> {
+        let session = load_session(&env, session_id)?;
+
+        session.streamer.require_auth();
+        require_not_paused(&env, PauseCategory::Reveal)?;
+
+        if session.phase != DrawPhase::Closed {
+            return Err(RouletteError::SessionNotClosed);
+        }
+        if env.ledger().sequence() > session.reveal_deadline {
+            return Err(RouletteError::RevealDeadlinePassed);
+        }
+        if session.config.multi_party
+            && session.nonces_revealed < session.num_participants
+            && env.ledger().sequence() <= session.nonce_deadline
+        {
+            return Err(RouletteError::NonceWindowOpen);
+        }
+        if lacks_nonces(&session) {
+            return Err(RouletteError::NotEnoughNonces);
+        }
+        if session.config.num_winners != 1 {
+            return Err(RouletteError::SingleWinnerOnly);
+        }
+        if session.commitment_scheme != CommitmentScheme::Poseidon {
+            return Err(RouletteError::PoseidonCommitmentRequired);
+        }
+        if eligible_pool_size(&session) > verifier::MAX_CIRCUIT_TICKETS {
+            return Err(RouletteError::TooManyTicketsForProof);
+        }
+
+        let vk: VerificationKey = env
+            .storage()
+            .instance()
+            .get(&DataKey::VerificationKey)
+            .ok_or(RouletteError::VerificationKeyNotSet)?;
+
+        // ── Entradas públicas contra el estado on-chain ────────────────────
+        if public_inputs.len() != verifier::NUM_PUBLIC_INPUTS {
+            return Err(RouletteError::WrongPublicInputCount);
+        }
+        if public_inputs.get(0).unwrap() != session.commitment {
+            return Err(RouletteError::CommitmentInputMismatch);
+        }
+        let pool_size = eligible_pool_size(&session);
+        if public_inputs.get(1).unwrap() != verifier::field_from_u32(&env, pool_size) {
+            return Err(RouletteError::ParticipantsInputMismatch);
+        }
+        let entropy = draw_entropy(&env, &session).to_array();
+        for (i, byte) in entropy.iter().enumerate() {
+            let expected = verifier::field_from_u32(&env, *byte as u32);
+            if public_inputs.get(2 + i as u32).unwrap() != expected {
+                return Err(RouletteError::EntropyInputMismatch);
+            }
+        }
+        let output = public_inputs.get(verifier::NUM_PUBLIC_INPUTS - 1).unwrap().to_array();
+        if output[..28].iter().any(|b| *b != 0) {
+            return Err(RouletteError::WinnerIndexOutOfBounds);
+        }
+        let drawn = u32::from_be_bytes([output[28], output[29], output[30], output[31]]);
+        if drawn >= pool_size {
+            return Err(RouletteError::WinnerIndexOutOfBounds);
+        }
+
+        // ── Verificación de la prueba ──────────────────────────────────────
+        if !verifier::verify(&env, &vk, &proof, &public_inputs) {
+            return Err(RouletteError::InvalidProof);
+        }
+
+        let winner = eligible_index(&env, session_id, &session, drawn)?;
+        let winner_indices = recheck_balance_gate(&env, session_id, &session, soroban_sdk::vec![&env, winner]);
+        settle_reveal(&env, session_id, session, winner_indices, 0, None)
+    }
+
+    // ════════════════════════════════════════════════════════════════════════
+    //  CANCELACIÓN
+    // ════════════════════════════════════════════════════════════════════════
+
+    /// El streamer cancela un sorteo abierto o cerrado sin reveal: el
+    /// premio vuelve a su cuenta y las entradas pagadas se reembolsan con
+    /// `claim_ticket_refund`.
+    ///
+    /// El evento lleva `CancelReason::AfterClose` si las inscripciones ya
+    /// estaban cerradas. Entonces el streamer ya puede calcular el ganador,
+    /// así que la fianza se confisca como en `expire_draw` y la
+    /// cancelación se cuenta en storage persistente (`get_late_cancellations`).
+    /// Antes del cierre la fianza vuelve al streamer.
+    ///
+    /// Vencido el plazo de reveal ya no se puede cancelar: la sesión sólo
+    /// sale por `expire_draw`, que confisca la fianza.
+    ///
+    /// # Arguments
+    /// * `session_id` — ID del sorteo
+    pub fn cancel_draw(env: Env, session_id: u32) -> Result<(), RouletteError> {
+        let session = load_session(&env, session_id)?;
+
+        session.streamer.require_auth();
+
+        let reason = match session.phase {
+            DrawPhase::Open => CancelReason::BeforeClose,
+            DrawPhase::Closed if env.ledger().sequence() <= session.reveal_deadline => {
+                CancelReason::AfterClose
+            }
+            _ => return Err(RouletteError::SessionNotCancellable),
+        };
+
+        cancel_session(&env, session_id, session, reason);
+
+        Ok(())
+    }
+
+    // ════════════════════════════════════════════════════════════════════════
+    //  EXPIRACIÓN — el streamer no reveló a tiempo
+    // ════════════════════════════════════════════════════════════════════════
+
+    /// Expira una sesión cuyo plazo de reveal venció sin reveal.
+    /// Puede llamarla cualquiera (no requiere firma): el streamer pierde
+    /// el sorteo, el premio custodiado vuelve a su cuenta, su fianza se
+    /// confisca y queda un evento on-chain que registra que no reveló.
+    /// Las entradas pagadas se reembolsan con `claim_ticket_refund`.
+    ///
+    /// En modo multi-party, si la ventana de nonces terminó con menos
+    /// nonces revelados que ganadores, el sorteo no puede hacerse (quien no
+    /// revela no entra): la sesión se cancela en su lugar sin esperar al
+    /// plazo de reveal (`CancelReason::NotEnoughNonces`), premio y fianza
+    /// vuelven al streamer y las entradas se reembolsan.
+    ///
+    /// Con los reveals pausados no se puede expirar: el streamer no tiene
+    /// cómo revelar, y la pausa retrasa su plazo lo que dure, así que al
+    /// despausar conserva el tiempo de reveal que le quedaba. El retraso
+    /// se corta en `MAX_REVEAL_DEADLINE_LEDGERS` desde `commit_draw`, para
+    /// que la sesión no caduque antes de su plazo; alcanzado ese tope se
+    /// puede expirar aunque la pausa siga.
+    ///
+    /// # Arguments
+    /// * `session_id` — ID del sorteo
+    pub fn expire_draw(env: Env, session_id: u32) -> Result<(), RouletteError> {
+        let mut session = load_session(&env, session_id)?;
+        if session.phase != DrawPhase::Closed {
+            return Err(RouletteError::SessionNotClosed);
+        }
+        // Una cancelación: no depende de la pausa de reveals
+        if lacks_nonces(&session) && env.ledger().sequence() > session.nonce_deadline {
+            cancel_session(&env, session_id, session, CancelReason::NotEnoughNonces);
+            return Ok(());
+        }
+
+        if session.reveal_deadline < latest_reveal_deadline(&session) {
+            require_not_paused(&env, PauseCategory::Reveal)?;
+        }
+        if env.ledger().sequence() <= session.reveal_deadline {
+            return Err(RouletteError::RevealDeadlineNotReached);
+        }
+
+        // Reembolso del premio custodiado
+        if session.prize_amount > 0 {
+            token::Client::new(&env, &session.prize_token).transfer(
+                &env.current_contract_address(),
+                &session.streamer,
+                &session.prize_amount,
+            );
+        }
+
+        slash_bond(&env, session_id, &mut session);
+
+        session.phase = DrawPhase::Expired;
+        save_session(&env, session_id, &session);
+        env.storage().temporary().extend_ttl(&DataKey::Session(session_id), TTL_LEDGERS, TTL_LEDGERS);
+
+        ExpiredEvent {
+            session_id,
+            streamer: session.streamer,
+            num_participants: session.num_participants,
+            reveal_deadline: session.reveal_deadline,
+            bond_slashed: session.config.bond.amount,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Un participante recupera su entrada de una sesión expirada o
+    /// cancelada.
+    ///
+    /// # Arguments
+    /// * `session_id`  — ID del sorteo
+    /// * `participant` — Cuenta registrada (firma requerida)
+    pub fn claim_ticket_refund(env: Env, session_id: u32, participant: Address) -> Result<(), RouletteError> {
+        participant.require_auth();
+
+        let session = load_session(&env, session_id)?;
+        if session.phase != DrawPhase::Expired && session.phase != DrawPhase::Cancelled {
+            return Err(RouletteError::SessionNotExpired);
+        }
+        let fee = session.config.entry_fee;
+        if fee.amount <= 0 {
+            return Err(RouletteError::NoTicketRefund);
+        }
+
+        let index: u32 = env
+            .storage()
+            .temporary()
+            .get(&DataKey::ParticipantIndex(session_id, participant.clone()))
+            .ok_or(RouletteError::NotRegistered)?;
+        let refunded_key = DataKey::TicketRefunded(session_id, index);
+        if env.storage().temporary().has(&refunded_key) {
+            return Err(RouletteError::TicketAlreadyRefunded);
+        }
+
+        set_temporary(&env, &refunded_key, &true);
+        token::Client::new(&env, &fee.token).transfer(
+            &env.current_contract_address(),
+            &participant,
+            &fee.amount,
+        );
+
+        Ok(())
+    }
+
+    /// Un participante de una sesión expirada o cancelada tras el cierre
+    /// cobra su parte de la fianza confiscada (si la sesión no tenía
+    /// `slash_recipient`).
+    /// Reparto a partes iguales; el resto de la división va al participante 0.
+    ///
+    /// # Arguments
+    /// * `session_id`  — ID del sorteo expirado o cancelado
+    /// * `participant` — Cuenta registrada (firma requerida)
+    pub fn claim_bond_share(env: Env, session_id: u32, participant: Address) -> Result<(), RouletteError> {
+        participant.require_auth();
+
+        let session = load_session(&env, session_id)?;
+        if !session.bond_slashed {
+            return Err(RouletteError::SessionNotExpired);
+        }
+        if session.config.bond.amount <= 0 || session.config.bond.slash_recipient.is_some() {
+            return Err(RouletteError::NoBondShare);
+        }
+
+        let index: u32 = env
+            .storage()
+            .temporary()
+            .get(&DataKey::ParticipantIndex(session_id, participant.clone()))
+            .ok_or(RouletteError::NotRegistered)?;
+        let claimed_key = DataKey::BondShareClaimed(session_id, index);
+        if env.storage().temporary().has(&claimed_key) {
+            return Err(RouletteError::BondShareAlreadyClaimed);
+        }
+
+        let count = session.num_participants as i128;
+        let share = session.config.bond.amount / count;
+        let amount = if index == 0 { share + session.config.bond.amount % count } else { share };
+        set_temporary(&env, &claimed_key, &true);
+        if amount > 0 {
+            token::Client::new(&env, &session.config.bond.token).transfer(
+                &env.current_contract_address(),
+                &participant,
+                &amount,
+            );
+        }
+
+        Ok(())
+    }
+
+    // ════════════════════════════════════════════════════════════════════════
+    //  GAME HUB — reintento del informe
+    // ════════════════════════════════════════════════════════════════════════
+
+    /// Reenvía al Game Hub las llamadas que fallaron tras el reveal,
+    /// desde la primera no aceptada (ver `hub_calls_done`). Puede llamarla
+    /// cualquiera: el resultado ya está fijado en el `DrawRecord`.
+    ///
+    /// # Returns
+    /// El estado del informe tras el intento (`Pending` si el Hub sigue
+    /// fallando)
+    pub fn retry_hub_report(env: Env, session_id: u32) -> Result<HubStatus, RouletteError> {
+        require_not_paused(&env, PauseCategory::Reveal)?;
+        let mut session = load_session(&env, session_id)?;
+        if session.hub_status != HubStatus::Pending {
+            return Err(RouletteError::HubReportNotPending);
+        }
+        let record = Self::get_draw_record(env.clone(), session_id)?;
+
+        report_to_hub(&env, session_id, &mut session, &record.winner_indices, &record.winner_addresses)?;
+
+        Ok(session.hub_status)
+    }
+
+    // ════════════════════════════════════════════════════════════════════════
+    //  CONSULTAS (read-only)
+    // ════════════════════════════════════════════════════════════════════════
+
+    /// Retorna la configuración con la que se creó una sesión
+    pub fn get_session_config(env: Env, session_id: u32) -> Result<SessionConfig, RouletteError> {
+        Ok(load_session(&env, session_id)?.config)
+    }
+
+    /// Retorna el estado de la sesión (para el frontend)
+    pub fn get_session(env: Env, session_id: u32) -> Result<DrawSession, RouletteError> {
+        load_session(&env, session_id)
+    }
+
+    /// Retorna la lista completa de participantes de una sesión.
+    /// Lee una entrada por participante: para listas grandes usar
+    /// `get_participants_page`.
+    pub fn get_participants(env: Env, session_id: u32) -> Result<Vec<Address>, RouletteError> {
+        let session = load_session(&env, session_id)?;
+        let mut participants: Vec<Address> = Vec::new(&env);
+        for index in 0..session.num_participants {
+            participants.push_back(participant_at(&env, session_id, index)?);
+        }
+        Ok(participants)
+    }
+
+    /// Retorna hasta `limit` participantes (máximo `MAX_PAGE`) desde el
+    /// índice `start`
+    pub fn get_participants_page(
+        env: Env,
+        session_id: u32,
+        start: u32,
+        limit: u32,
+    ) -> Result<Vec<Address>, RouletteError> {
+        let session = load_session(&env, session_id)?;
+        let end = start.saturating_add(limit.min(MAX_PAGE)).min(session.num_participants);
+        let mut page: Vec<Address> = Vec::new(&env);
+        for index in start..end {
+            page.push_back(participant_at(&env, session_id, index)?);
+        }
+        Ok(page)
+    }
+
+    /// Retorna el índice de `participant` en la sesión, si está inscrito
+    pub fn get_participant_index(env: Env, session_id: u32, participant: Address) -> Option<u32> {
+        env.storage()
+            .temporary()
+            .get(&DataKey::ParticipantIndex(session_id, participant))
+    }
+
+    /// Retorna el peso (boletos) de un participante; None si no está inscrito
+    pub fn get_participant_weight(env: Env, session_id: u32, participant: Address) -> Option<u32> {
+        let session = load_session(&env, session_id).ok()?;
+        let index: u32 = env
+            .storage()
+            .temporary()
+            .get(&DataKey::ParticipantIndex(session_id, participant))?;
+        Some(weights::weight_of(&env, session.weight_tree, index))
+    }
+
+    /// Retorna las fianzas pendientes de un streamer, agregadas por token
+    pub fn get_outstanding_bonds(env: Env, streamer: Address) -> Map<Address, i128> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::StreamerBonds(streamer))
+            .unwrap_or_else(|| Map::new(&env))
+    }
+
+    /// Retorna las sesiones de un streamer cuya fianza sigue custodiada
+    pub fn get_bonded_sessions(env: Env, streamer: Address) -> Vec<u32> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::BondedSessions(streamer))
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Retorna el número de participantes registrados
+    pub fn participant_count(env: Env, session_id: u32) -> Result<u32, RouletteError> {
+        let session = load_session(&env, session_id)?;
+        Ok(session.num_participants)
+    }
+
+    /// Retorna el registro archivado de un sorteo revelado
+    pub fn get_draw_record(env: Env, session_id: u32) -> Result<DrawRecord, RouletteError> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::DrawRecord(session_id))
+            .ok_or(RouletteError::DrawRecordNotFound)
+    }
+
+    /// Retorna el número de sorteos archivados de un streamer
+    pub fn get_draw_count(env: Env, streamer: Address) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::StreamerDrawCount(streamer))
+            .unwrap_or(0)
+    }
+
+    /// Retorna cuántos sorteos canceló un streamer tras cerrar inscripciones
+    pub fn get_late_cancellations(env: Env, streamer: Address) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::StreamerLateCancels(streamer))
+            .unwrap_or(0)
+    }
+
+    /// Retorna una página del historial de un streamer (session_ids en
+    /// orden de reveal), desde la posición `start` y con hasta `limit`
+    /// entradas (máximo `MAX_PAGE`)
+    pub fn get_draw_history(env: Env, streamer: Address, start: u32, limit: u32) -> Vec<u32> {
+        let count = Self::get_draw_count(env.clone(), streamer.clone());
+        let end = start.saturating_add(limit.min(MAX_PAGE)).min(count);
+        let mut page: Vec<u32> = Vec::new(&env);
+        for i in start..end {
+            if let Some(id) = env
+                .storage()
+                .persistent()
+                .get(&DataKey::StreamerDraw(streamer.clone(), i))
+            {
+                page.push_back(id);
+            }
+        }
+        page
+    }
+
+    /// Retorna el admin actual
+    pub fn get_admin(env: Env) -> Result<Address, RouletteError> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(RouletteError::NotInitialized)
+    }
+
+    /// Retorna las categorías pausadas
+    pub fn get_pause_state(env: Env) -> PauseState {
+        pause_state(&env)
+    }
+
+    /// Retorna la versión del layout de storage
+    pub fn get_version(env: Env) -> u32 {
+        storage_version(&env)
+    }
+}
+
+// ─── Administración y Migraciones ───────────────────────────────────────────
+
+/// Exige la firma del admin
+fn require_admin(env: &Env) -> Result<Address, RouletteError> {
+    let admin: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .ok_or(RouletteError::NotInitialized)?;
+    admin.require_auth();
+    Ok(admin)
+}
+
+/// Categorías pausadas (ninguna si nunca se pausó)
+fn pause_state(env: &Env) -> PauseState {
+    env.storage().instance().get(&DataKey::Pause).unwrap_or_default()
+}
+
+/// Falla con `Paused` si `category` está pausada
+fn require_not_paused(env: &Env, category: PauseCategory) -> Result<(), RouletteError> {
+    let state = pause_state(env);
+    let paused = match category {
+        PauseCategory::Creation => state.creation,
+        PauseCategory::Registration => state.registration,
+        PauseCategory::Reveal => state.reveal,
+    };
+    if paused {
+        return Err(RouletteError::Paused);
+    }
+    Ok(())
+}
+
+/// Marca o desmarca `category` y emite el evento si cambió
+fn set_paused(env: &Env, category: PauseCategory, paused: bool) {
+    let mut state = pause_state(env);
+    let flag = match category {
+        PauseCategory::Creation => &mut state.creation,
+        PauseCategory::Registration => &mut state.registration,
+        PauseCategory::Reveal => &mut state.reveal,
+    };
+    if *flag == paused {
+        return;
+    }
+    *flag = paused;
+    if category == PauseCategory::Reveal {
+        let now = env.ledger().sequence();
+        if paused {
+            state.reveal_paused_at = now;
+        } else {
+            state.reveal_paused_ledgers += now - state.reveal_paused_at;
+        }
+    }
+    env.storage().instance().set(&DataKey::Pause, &state);
+
+    if paused {
+        PausedEvent { category }.publish(env);
+    } else {
+        UnpausedEvent { category }.publish(env);
+    }
+}
+
+/// Ledgers que los reveals llevan pausados desde el despliegue, contando
+/// la pausa en curso. Sólo avanza mientras `PauseCategory::Reveal` está activa
+fn reveal_pause_clock(env: &Env) -> u32 {
+    let state = pause_state(env);
+    let mut clock = state.reveal_paused_ledgers;
+    if state.reveal {
+        clock += env.ledger().sequence() - state.reveal_paused_at;
+    }
+    clock
+}
+
+/// Versión guardada del storage; 0 si no se inicializó
+fn storage_version(env: &Env) -> u32 {
+    env.storage().instance().get(&DataKey::Version).unwrap_or(0)
+}
+
+/// Migra el storage persistente y de instancia de `from` a `from + 1`.
+///
+/// Cada versión nueva añade aquí su rama (renombrar claves, rellenar
+/// campos nuevos, ...). Una versión sin rama falla con
+/// `UnknownStorageVersion` en vez de darse por migrada.
+fn migrate_step(_env: &Env, from: u32) -> Result<(), RouletteError> {
+    match from {
+        // Storage de la versión 1 al que le falta `DataKey::Version`
+        // (`initialize` la guarda). Un despliegue anterior al versionado
+        // (sesión sin `StoredSession`, lista `Participants`, sin admin) no
+        // llega aquí: sin admin `migrate` no puede autorizarse
+        0 => Ok(()),
+        _ => Err(RouletteError::UnknownStorageVersion),
+    }
+}
+
+// ─── Storage de Sesiones ────────────────────────────────────────────────────
+
+/// Reserva el siguiente ID para `create_draw`. El rango es exclusivo de
+/// `create_draw`, así que el contador no comprueba si el ID está en uso.
+/// Un contador anterior al rango reservado (IDs desde 1) salta a su inicio.
+fn allocate_session_id(env: &Env) -> Result<u32, RouletteError> {
+    let session_id: u32 = env
+        .storage()
+        .instance()
+        .get(&DataKey::NextSessionId)
+        .unwrap_or(AUTO_SESSION_ID_START)
+        .max(AUTO_SESSION_ID_START);
+    if session_id >= HUB_SESSION_ID_START {
+        return Err(RouletteError::SessionIdsExhausted);
+    }
+    env.storage().instance().set(&DataKey::NextSessionId, &(session_id + 1));
+    Ok(session_id)
+}
+
+/// Reserva un árbol de pesos nuevo para una sesión
+fn allocate_weight_tree(env: &Env) -> u32 {
+    let tree: u32 = env.storage().instance().get(&DataKey::NextWeightTree).unwrap_or(0);
+    env.storage().instance().set(&DataKey::NextWeightTree, &(tree + 1));
+    tree
+}
+
+/// Crea la sesión `session_id` (común a `commit_draw` y `create_draw`):
+/// valida, custodia premio y fianza y guarda la sesión abierta
+fn open_session(
+    env: &Env,
+    streamer: Address,
+    session_id: u32,
+    commitment: BytesN<32>,
+    commitment_scheme: CommitmentScheme,
+    prize_token: Address,
+    prize_amount: i128,
+    config: SessionConfig,
+) -> Result<(), RouletteError> {
+    // Solo el streamer puede hacer commit
+    streamer.require_auth();
+    require_not_paused(env, PauseCategory::Creation)?;
+
+    // Una sesión con ese ID no debe existir, ni viva ni archivada
+    if env.storage().temporary().has(&DataKey::Session(session_id))
+        || env.storage().persistent().has(&DataKey::DrawRecord(session_id))
+    {
+        return Err(RouletteError::SessionAlreadyExists);
+    }
+    if prize_amount < 0 {
+        return Err(RouletteError::NegativePrizeAmount);
+    }
+    if commitment_scheme == CommitmentScheme::Poseidon
+        && !poseidon::is_field_element(&commitment)
+    {
+        return Err(RouletteError::CommitmentNotInField);
+    }
+    validate_config(env, &config)?;
+
+    // Escrow: mover el premio al contrato antes de abrir inscripciones
+    if prize_amount > 0 {
+        token::Client::new(env, &prize_token).transfer(
+            &streamer,
+            env.current_contract_address(),
+            &prize_amount,
+        );
+    }
+    let bond = &config.bond;
+    if bond.amount > 0 {
+        token::Client::new(env, &bond.token).transfer(
+            &streamer,
+            env.current_contract_address(),
+            &bond.amount,
+        );
+        add_outstanding_bond(env, &streamer, session_id, bond);
+    }
+
+    let session = DrawSession {
+        streamer,
+        commitment: commitment.clone(),
+        commitment_scheme,
+        num_participants: 0,
+        phase: DrawPhase::Open,
+        winner_indices: Vec::new(env),
+        prize_token,
+        prize_amount,
+        reveal_deadline: 0,
+        participants_hash: BytesN::from_array(env, &[0u8; 32]),
+        close_entropy: BytesN::from_array(env, &[0u8; 32]),
+        nonce_deadline: 0,
+        nonces_revealed: 0,
+        participant_entropy: BytesN::from_array(env, &[0u8; 32]),
+        entry_pool: 0,
+        total_weight: 0,
+        config,
+        hub_status: HubStatus::Unreported,
+        hub_calls_done: 0,
+        hub_first_id: 0,
+        weight_tree: allocate_weight_tree(env),
+        reveal_clock: 0,
+        bond_slashed: false,
+        opened_at: env.ledger().sequence(),
+    };
+
+    // Guardar sesión; los participantes se guardan uno por entrada
+    save_session(env, session_id, &session);
+
+    // Establecer TTL de 30 días
+    env.storage().temporary().extend_ttl(&DataKey::Session(session_id), TTL_LEDGERS, TTL_LEDGERS);
+
+    // Evento: fase Commit iniciada
+    CommittedEvent { session_id, commitment }.publish(env);
+
+    Ok(())
+}
+
+
+/// Lee una sesión y la convierte al layout actual de `DrawSession`.
+/// Si está cerrada, retrasa sus plazos lo que los reveals hayan estado
+/// pausados desde que se calcularon (se persiste cuando el llamador guarda).
+/// El retraso no pasa de `opened_at + MAX_REVEAL_DEADLINE_LEDGERS`: las
+/// entradas de participantes no pueden extenderse todas y caducarían
+/// antes del plazo, dejando premio y fianza bloqueados.
+fn load_session(env: &Env, session_id: u32) -> Result<DrawSession, RouletteError> {
+    let stored: StoredSession = env
+        .storage()
+        .temporary()
+        .get(&DataKey::Session(session_id))
+        .ok_or(RouletteError::SessionNotFound)?;
+    let StoredSession::V1(mut session) = stored;
+    if session.phase == DrawPhase::Closed {
+        let clock = reveal_pause_clock(env);
+        let latest = latest_reveal_deadline(&session);
+        let shift = (clock - session.reveal_clock).min(latest.saturating_sub(session.reveal_deadline));
+        if shift > 0 {
+            if session.config.multi_party {
+                session.nonce_deadline += shift;
+            }
+            session.reveal_deadline += shift;
+        }
+        session.reveal_clock = clock;
+    }
+    Ok(session)
+}
+
+/// Tope de los plazos que desplaza una pausa de reveals (ver `load_session`)
+fn latest_reveal_deadline(session: &DrawSession) -> u32 {
+    session.opened_at.saturating_add(MAX_REVEAL_DEADLINE_LEDGERS)
+}
+
+/// Añade `participant` al final de la lista y encadena su hash.
+/// No comprueba duplicados ni el límite: eso es cosa del llamador.
+fn append_participant(env: &Env, session_id: u32, session: &mut DrawSession, participant: &Address) {
+    let index = session.num_participants;
+
+    let mut link = Bytes::from_array(env, &session.participants_hash.to_array());
+    link.append(&participant.clone().to_xdr(env));
+    session.participants_hash = env.crypto().sha256(&link).into();
+
+    set_temporary(env, &DataKey::Participant(session_id, index), participant);
+    set_temporary(env, &DataKey::ParticipantIndex(session_id, participant.clone()), &index);
+    session.num_participants = index + 1;
+    session.total_weight += 1;
+}
+
+/// Quita al participante `index` (`participant`) de la lista: el último
+/// ocupa su índice junto con su compromiso de nonce y su peso. Devuelve
+/// el índice que se movió (igual a `index` si era el último).
+fn remove_participant(
+    env: &Env,
+    session_id: u32,
+    session: &mut DrawSession,
+    index: u32,
+    participant: &Address,
+) -> Result<u32, RouletteError> {
+    let last = session.num_participants - 1;
+    let storage = env.storage().temporary();
+
+    let mut link = Bytes::from_array(env, &session.participants_hash.to_array());
+    link.push_back(0xff);
+    link.append(&participant.clone().to_xdr(env));
+    session.participants_hash = env.crypto().sha256(&link).into();
+
+    let weight = weights::weight_of(env, session.weight_tree, index);
+    weights::move_last(env, session.weight_tree, session.config.max_participants, index, last);
+    if index != last {
+        let moved = participant_at(env, session_id, last)?;
+        set_temporary(env, &DataKey::Participant(session_id, index), &moved);
+        set_temporary(env, &DataKey::ParticipantIndex(session_id, moved), &index);
+        if let Some(nonce_commitment) = storage.get::<_, BytesN<32>>(&DataKey::NonceCommitment(session_id, last)) {
+            set_temporary(env, &DataKey::NonceCommitment(session_id, index), &nonce_commitment);
+        }
+    }
+    storage.remove(&DataKey::Participant(session_id, last));
+    storage.remove(&DataKey::NonceCommitment(session_id, last));
+    storage.remove(&DataKey::ParticipantIndex(session_id, participant.clone()));
+
+    session.num_participants = last;
+    session.total_weight -= weight;
+    Ok(last)
+}
+
+/// Participante `index` de una sesión
+fn participant_at(env: &Env, session_id: u32, index: u32) -> Result<Address, RouletteError> {
+    env.storage()
+        .temporary()
+        .get(&DataKey::Participant(session_id, index))
+        .ok_or(RouletteError::SessionNotFound)
+}
+
+/// Guarda una entrada temporal de la sesión con el TTL estándar
+fn set_temporary<V: IntoVal<Env, Val>>(env: &Env, key: &DataKey, value: &V) {
+    env.storage().temporary().set(key, value);
+    env.storage().temporary().extend_ttl(key, TTL_LEDGERS, TTL_LEDGERS);
+}
+
+/// Guarda una sesión con la versión actual de su layout
+fn save_session(env: &Env, session_id: u32, session: &DrawSession) {
+    env.storage()
+        .temporary()
+        .set(&DataKey::Session(session_id), &StoredSession::V1(session.clone()));
+}
+
+// ─── Configuración de Sesión ────────────────────────────────────────────────
+
+/// Valida una `SessionConfig` contra los límites del contrato
+fn validate_config(env: &Env, config: &SessionConfig) -> Result<(), RouletteError> {
+    if config.num_winners == 0 || config.num_winners > MAX_WINNERS {
+        return Err(RouletteError::InvalidNumberOfWinners);
+    }
+    if config.min_participants < 2
+        || config.max_participants < config.min_participants
+        || config.max_participants < config.num_winners
+        || config.max_participants > MAX_PARTICIPANTS
+    {
+        return Err(RouletteError::InvalidSessionConfig);
+    }
+    let windows = [config.reveal_window_ledgers, config.nonce_window_ledgers];
+    if windows.iter().any(|w| *w < MIN_WINDOW_LEDGERS || *w > MAX_WINDOW_LEDGERS) {
+        return Err(RouletteError::InvalidSessionConfig);
+    }
+    // Sin plazo ni cierre anticipado la sesión sólo cerraría al llenarse
+    if config.registration_deadline == 0 && !config.allow_early_close {
+        return Err(RouletteError::InvalidSessionConfig);
+    }
+    let now = env.ledger().sequence();
+    let deadline = config.registration_deadline;
+    if deadline != 0 && (deadline <= now || deadline - now > MAX_REGISTRATION_LEDGERS) {
+        return Err(RouletteError::InvalidRegistrationDeadline);
+    }
+    if config.bond.amount < 0 {
+        return Err(RouletteError::NegativeBondAmount);
+    }
+    let fee = &config.entry_fee;
+    if fee.amount < 0
+        || fee.protocol_fee_bps > MAX_PROTOCOL_FEE_BPS
+        || fee.streamer_cut_bps as i128 + fee.protocol_fee_bps as i128 > BPS_DENOMINATOR
+    {
+        return Err(RouletteError::InvalidEntryFee);
+    }
+    if config.balance_gate.min_balance < 0 {
+        return Err(RouletteError::InvalidBalanceGate);
+    }
+    Ok(())
+}
+
+// ─── Cierre de Inscripciones ────────────────────────────────────────────────
+
+/// Si el plazo de inscripción programado ya venció
+fn registration_deadline_passed(env: &Env, session: &DrawSession) -> bool {
+    let deadline = session.config.registration_deadline;
+    deadline != 0 && env.ledger().sequence() > deadline
+}
+
+/// Si la lista admite cambios: sesión abierta y plazo sin vencer
+fn registration_open(env: &Env, session: &DrawSession) -> bool {
+    session.phase == DrawPhase::Open && !registration_deadline_passed(env, session)
+}
+
+/// Si el cierre programado ya puede ejecutarse (plazo vencido o tope
+/// alcanzado)
+fn close_due(env: &Env, session: &DrawSession) -> bool {
+    registration_deadline_passed(env, session) || session.num_participants >= session.config.max_participants
+}
+
+/// Cierre común de `close_registrations` y `finalize_close`: fija la
+/// entropía de cierre y los plazos de nonces y reveal.
+fn close_session(env: &Env, session_id: u32, mut session: DrawSession) -> Result<(), RouletteError> {
+    if session.num_participants < session.config.min_participants {
+        return Err(RouletteError::NotEnoughParticipants);
+    }
+    if session.num_participants < session.config.num_winners {
+        return Err(RouletteError::FewerParticipantsThanWinners);
+    }
+
+    let prng_bytes: BytesN<32> = env.prng().gen();
+    let mut entropy_preimage = Bytes::from_array(env, &prng_bytes.to_array());
+    entropy_preimage.extend_from_array(&env.ledger().sequence().to_be_bytes());
+    entropy_preimage.extend_from_array(&session.participants_hash.to_array());
+    session.close_entropy = env.crypto().sha256(&entropy_preimage).into();
+
+    session.phase = DrawPhase::Closed;
+    let mut reveal_from = env.ledger().sequence();
+    if session.config.multi_party {
+        session.nonce_deadline = reveal_from + session.config.nonce_window_ledgers;
+        reveal_from = session.nonce_deadline;
+    }
+    session.reveal_deadline = reveal_from + session.config.reveal_window_ledgers;
+    session.reveal_clock = reveal_pause_clock(env);
+    save_session(env, session_id, &session);
+
+    ClosedEvent {
+        session_id,
+        num_participants: session.num_participants,
+        reveal_deadline: session.reveal_deadline,
+    }
+    .publish(env);
+
+    Ok(())
+}
+
+// ─── Cancelación ─────────────────────────────────────────────────────────────
+
+/// Cancela una sesión abierta o cerrada: devuelve el premio al streamer
+/// y deja las entradas para `claim_ticket_refund`. La fianza vuelve al
+/// streamer salvo con `AfterClose`, que la confisca y cuenta como
+/// cancelación tardía.
+fn cancel_session(env: &Env, session_id: u32, mut session: DrawSession, reason: CancelReason) {
+    if session.prize_amount > 0 {
+        token::Client::new(env, &session.prize_token).transfer(
+            &env.current_contract_address(),
+            &session.streamer,
+            &session.prize_amount,
+        );
+    }
+    if reason == CancelReason::AfterClose {
+        slash_bond(env, session_id, &mut session);
+    } else if session.config.bond.amount > 0 {
+        token::Client::new(env, &session.config.bond.token).transfer(
+            &env.current_contract_address(),
+            &session.streamer,
+            &session.config.bond.amount,
+        );
+        remove_outstanding_bond(env, &session.streamer, session_id, &session.config.bond);
+    }
+
+    if reason == CancelReason::AfterClose {
+        let storage = env.storage().persistent();
+        let max_ttl = env.storage().max_ttl();
+        let key = DataKey::StreamerLateCancels(session.streamer.clone());
+        let count: u32 = storage.get(&key).unwrap_or(0);
+        storage.set(&key, &(count + 1));
+        storage.extend_ttl(&key, max_ttl, max_ttl);
+    }
+
+    session.phase = DrawPhase::Cancelled;
+    save_session(env, session_id, &session);
+    env.storage().temporary().extend_ttl(&DataKey::Session(session_id), TTL_LEDGERS, TTL_LEDGERS);
+
+    CancelledEvent {
+        session_id,
+        reason,
+        streamer: session.streamer,
+        num_participants: session.num_participants,
+    }
+    .publish(env);
+}
+
+/// Confisca la fianza de una sesión cerrada que no se reveló (expirada o
+/// cancelada tras el cierre): va a `slash_recipient` o, sin él, queda en
+/// el contrato para que cada participante reclame su parte con
+/// `claim_bond_share` (la lista puede ser demasiado larga para pagarla
+/// en una transacción).
+fn slash_bond(env: &Env, session_id: u32, session: &mut DrawSession) {
+    session.bond_slashed = true;
+    if session.config.bond.amount <= 0 {
+        return;
+    }
+    if let Some(recipient) = &session.config.bond.slash_recipient {
+        token::Client::new(env, &session.config.bond.token).transfer(
+            &env.current_contract_address(),
+            recipient,
+            &session.config.bond.amount,
+        );
+    }
+    remove_outstanding_bond(env, &session.streamer, session_id, &session.config.bond);
+}
+
+// ─── Reveal ─────────────────────────────────────────────────────────────────
+
+/// Cierre común de `reveal_winner` y `reveal_winner_with_proof` una vez
+/// fijados los ganadores: Game Hub, pago del premio, devolución de la
+/// fianza, persistencia, archivo y evento. `secret_number` es 0 y `salt`
+/// None si el reveal se hizo con prueba (el secreto no sale del cliente).
+fn settle_reveal(
+    env: &Env,
+    session_id: u32,
+    mut session: DrawSession,
+    winner_indices: Vec<u32>,
+    secret_number: u64,
+    salt: Option<BytesN<32>>,
+) -> Result<(), RouletteError> {
+    // Sólo se leen las entradas ganadoras, no la lista completa
+    let mut winner_addresses: Vec<Address> = Vec::new(env);
+    for idx in winner_indices.iter() {
+        if idx >= session.num_participants {
+            return Err(RouletteError::WinnerIndexOutOfBounds);
+        }
+        winner_addresses.push_back(participant_at(env, session_id, idx)?);
+    }
+
+    // ── 3. Pago del premio custodiado ──────────────────────────────────
+    //
+    //  Se ejecuta en la misma transacción que el reveal: si alguna
+    //  transferencia falla, el reveal completo se revierte.
+    //  Reparto a partes iguales; el resto de la división va al primer
+    //  ganador para no dejar fondos atrapados en el contrato.
+    pay_winners(env, &session.prize_token, session.prize_amount, &winner_addresses);
+
+    // ── 3b. Bote de entradas ───────────────────────────────────────────
+    //
+    //  Comisión del protocolo y parte del streamer (redondeadas hacia
+    //  abajo); el resto se reparte entre los ganadores como el premio.
+    if session.entry_pool > 0 {
+        let fee = &session.config.entry_fee;
+        let pool = session.entry_pool;
+        let protocol_fee = pool * fee.protocol_fee_bps as i128 / BPS_DENOMINATOR;
+        let streamer_cut = pool * fee.streamer_cut_bps as i128 / BPS_DENOMINATOR;
+        let entry_token = token::Client::new(env, &fee.token);
+        if protocol_fee > 0 {
+            entry_token.transfer(&env.current_contract_address(), &fee_recipient(env)?, &protocol_fee);
+        }
+        if streamer_cut > 0 {
+            entry_token.transfer(&env.current_contract_address(), &session.streamer, &streamer_cut);
+        }
+        pay_winners(env, &fee.token, pool - protocol_fee - streamer_cut, &winner_addresses);
+    }
+
+    // ── 4. Devolución de la fianza ─────────────────────────────────────
+    if session.config.bond.amount > 0 {
+        token::Client::new(env, &session.config.bond.token).transfer(
+            &env.current_contract_address(),
+            &session.streamer,
+            &session.config.bond.amount,
+        );
+        remove_outstanding_bond(env, &session.streamer, session_id, &session.config.bond);
+    }
+
+    // ── 5. Persistir resultado y emitir evento ─────────────────────────
+    //
+    //  Antes de llamar al Hub: su disponibilidad no decide el sorteo.
+    session.phase = DrawPhase::Revealed;
+    session.winner_indices = winner_indices.clone();
+    session.hub_status = if session.config.report_to_hub {
+        HubStatus::Pending
+    } else {
+        HubStatus::Disabled
+    };
+    save_session(env, session_id, &session);
+
+    archive_draw(
+        env,
+        session_id,
+        &DrawRecord {
+            streamer: session.streamer.clone(),
+            commitment: session.commitment.clone(),
+            commitment_scheme: session.commitment_scheme.clone(),
+            secret_number,
+            salt,
+            participants_hash: session.participants_hash.clone(),
+            num_participants: session.num_participants,
+            total_weight: session.total_weight,
+            draw_entropy: draw_entropy(env, &session),
+            winner_indices: winner_indices.clone(),
+            winner_addresses: winner_addresses.clone(),
+            prize_token: session.prize_token.clone(),
+            prize_amount: session.prize_amount,
+            revealed_ledger: env.ledger().sequence(),
+        },
+    );
+
+    WinnerEvent {
+        session_id,
+        winner_indices: winner_indices.clone(),
+        winner_addresses: winner_addresses.clone(),
+        secret_number,
+        num_participants: session.num_participants,
+        prize_token: session.prize_token.clone(),
+        prize_amount: session.prize_amount,
+    }
+    .publish(env);
+
+    // ── 6. Informe al Game Hub ─────────────────────────────────────────
+    if session.hub_status == HubStatus::Pending {
+        report_to_hub(env, session_id, &mut session, &winner_indices, &winner_addresses)?;
+    }
+
+    Ok(())
+}
+
+/// Reparte `total` de `token` a partes iguales entre `winners`; el resto
+/// de la división va al primer ganador para no dejar fondos atrapados.
+fn pay_winners(env: &Env, token: &Address, total: i128, winners: &Vec<Address>) {
+    if total <= 0 {
+        return;
+    }
+    let client = token::Client::new(env, token);
+    let count = winners.len() as i128;
+    let share = total / count;
+    let remainder = total % count;
+    for (k, addr) in winners.iter().enumerate() {
+        let amount = if k == 0 { share + remainder } else { share };
+        if amount > 0 {
+            client.transfer(&env.current_contract_address(), &addr, &amount);
+        }
+    }
+}
+
+/// Destino de la comisión del protocolo
+fn fee_recipient(env: &Env) -> Result<Address, RouletteError> {
+    match env.storage().instance().get(&DataKey::FeeRecipient) {
+        Some(recipient) => Ok(recipient),
+        None => env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(RouletteError::NotInitialized),
+    }
+}
+
+/// Cobra la entrada de `participant` (si el sorteo la tiene)
+fn charge_entry_fee(env: &Env, session: &mut DrawSession, participant: &Address) {
+    let fee = &session.config.entry_fee;
+    if fee.amount > 0 {
+        token::Client::new(env, &fee.token).transfer(
+            participant,
+            env.current_contract_address(),
+            &fee.amount,
+        );
+        session.entry_pool += fee.amount;
+    }
+}
+
+// ─── Archivo de Sorteos ─────────────────────────────────────────────────────
+
+/// Guarda `record` y lo añade al historial del streamer. Las entradas
+/// persistentes no se borran al vencer su TTL (se archivan y pueden
+/// restaurarse); se extienden al máximo que permita la red.
+fn archive_draw(env: &Env, session_id: u32, record: &DrawRecord) {
+    let storage = env.storage().persistent();
+    let max_ttl = env.storage().max_ttl();
+
+    let record_key = DataKey::DrawRecord(session_id);
+    storage.set(&record_key, record);
+    storage.extend_ttl(&record_key, max_ttl, max_ttl);
+
+    let count_key = DataKey::StreamerDrawCount(record.streamer.clone());
+    let count: u32 = storage.get(&count_key).unwrap_or(0);
+    let entry_key = DataKey::StreamerDraw(record.streamer.clone(), count);
+    storage.set(&entry_key, &session_id);
+    storage.set(&count_key, &(count + 1));
+    storage.extend_ttl(&entry_key, max_ttl, max_ttl);
+    storage.extend_ttl(&count_key, max_ttl, max_ttl);
+}
+
+// ─── Contabilidad de Fianzas ────────────────────────────────────────────────
+
+/// Registra la fianza de `session_id` como pendiente para el streamer
+fn add_outstanding_bond(env: &Env, streamer: &Address, session_id: u32, bond: &StreamerBond) {
+    let bonds_key = DataKey::StreamerBonds(streamer.clone());
+    let sessions_key = DataKey::BondedSessions(streamer.clone());
+
+    let mut bonds: Map<Address, i128> =
+        env.storage().persistent().get(&bonds_key).unwrap_or_else(|| Map::new(env));
+    let current = bonds.get(bond.token.clone()).unwrap_or(0);
+    bonds.set(bond.token.clone(), current + bond.amount);
+
+    let mut sessions: Vec<u32> =
+        env.storage().persistent().get(&sessions_key).unwrap_or_else(|| Vec::new(env));
+    sessions.push_back(session_id);
+
+    env.storage().persistent().set(&bonds_key, &bonds);
+    env.storage().persistent().set(&sessions_key, &sessions);
+    env.storage().persistent().extend_ttl(&bonds_key, TTL_LEDGERS, TTL_LEDGERS);
+    env.storage().persistent().extend_ttl(&sessions_key, TTL_LEDGERS, TTL_LEDGERS);
+}
+
+/// Libera la fianza de `session_id` (devuelta o confiscada)
+fn remove_outstanding_bond(env: &Env, streamer: &Address, session_id: u32, bond: &StreamerBond) {
+    let bonds_key = DataKey::StreamerBonds(streamer.clone());
+    let sessions_key = DataKey::BondedSessions(streamer.clone());
+
+    let mut bonds: Map<Address, i128> =
+        env.storage().persistent().get(&bonds_key).unwrap_or_else(|| Map::new(env));
+    let remaining = bonds.get(bond.token.clone()).unwrap_or(0) - bond.amount;
+    if remaining > 0 {
+        bonds.set(bond.token.clone(), remaining);
+    } else {
+        bonds.remove(bond.token.clone());
+    }
+
+    let mut sessions: Vec<u32> =
+        env.storage().persistent().get(&sessions_key).unwrap_or_else(|| Vec::new(env));
+    if let Some(pos) = sessions.first_index_of(session_id) {
+        sessions.remove(pos);
+    }
+
+    if bonds.is_empty() {
+        env.storage().persistent().remove(&bonds_key);
+    } else {
+        env.storage().persistent().set(&bonds_key, &bonds);
+    }
+    if sessions.is_empty() {
+        env.storage().persistent().remove(&sessions_key);
+    } else {
+        env.storage().persistent().set(&sessions_key, &sessions);
+    }
+}
+
+// ─── Modo Multi-Party ───────────────────────────────────────────────────────
+
+/// Entropía que entra en la derivación del ganador.
+/// Sin modo multi-party es `close_entropy`; con él,
+/// SHA-256(close_entropy || participant_entropy).
+fn draw_entropy(env: &Env, session: &DrawSession) -> BytesN<32> {
+    if !session.config.multi_party {
+        return session.close_entropy.clone();
+    }
+    let mut preimage = Bytes::from_array(env, &session.close_entropy.to_array());
+    preimage.extend_from_array(&session.participant_entropy.to_array());
+    env.crypto().sha256(&preimage).into()
+}
+
+/// Si la extracción se limita a quienes revelaron su nonce: siempre en
+/// modo multi-party. Quien no revela nunca entra, para que callar un
+/// nonce no cambie el pool a favor de nadie.
+fn only_revealed_nonces(session: &DrawSession) -> bool {
+    session.config.multi_party
+}
+
+/// Multi-party con menos nonces revelados que ganadores: el sorteo no
+/// puede completarse y la sesión sólo sale cancelada (ver `expire_draw`)
+fn lacks_nonces(session: &DrawSession) -> bool {
+    session.config.multi_party && session.nonces_revealed < session.config.num_winners
+}
+
+/// Si algún participante tiene un peso distinto de 1
+fn is_weighted(session: &DrawSession) -> bool {
+    session.total_weight > session.num_participants
+}
+
+/// Tamaño del pool sobre el que se extraen los ganadores (boletos si
+/// la sesión tiene pesos)
+fn eligible_pool_size(session: &DrawSession) -> u32 {
+    if is_weighted(session) {
+        session.total_weight
+    } else if only_revealed_nonces(session) {
+        session.nonces_revealed
+    } else {
+        session.num_participants
+    }
+}
+
+/// Índice de participante en la posición `position` del pool.
+/// Con nonces el pool está en orden de `reveal_nonce`; con pesos la
+/// posición es un boleto; si no, es la lista completa y la posición
+/// coincide con el índice.
+fn eligible_index(
+    env: &Env,
+    session_id: u32,
+    session: &DrawSession,
+    position: u32,
+) -> Result<u32, RouletteError> {
+    if is_weighted(session) {
+        let (index, _) =
+            weights::locate(env, session.weight_tree, session.num_participants, position as u64, &Vec::new(env));
+        return Ok(index);
+    }
+    if !only_revealed_nonces(session) {
+        return Ok(position);
+    }
+    env.storage()
+        .temporary()
+        .get(&DataKey::EligibleIndex(session_id, position))
+        .ok_or(RouletteError::SessionNotFound)
+}
+
+// ─── Saldo Mínimo ───────────────────────────────────────────────────────────
+
+/// Si `account` cumple el requisito de saldo de la sesión
+fn holds_min_balance(env: &Env, gate: &BalanceGate, account: &Address) -> bool {
+    gate.min_balance == 0 || token::Client::new(env, &gate.token).balance(account) >= gate.min_balance
+}
+
+/// Sustituye a los ganadores que ya no tienen el saldo exigido, si la
+/// sesión lo pide (ver `BalanceGate`). El sustituto es el siguiente
+/// índice, en orden circular, que entra en la extracción (con nonces,
+/// quien lo reveló), no fue extraído y cumple el saldo.
+fn recheck_balance_gate(env: &Env, session_id: u32, session: &DrawSession, drawn: Vec<u32>) -> Vec<u32> {
+    let gate = &session.config.balance_gate;
+    if gate.min_balance == 0 || !gate.recheck_at_reveal {
+        return drawn;
+    }
+    let n = session.num_participants;
+    let holds = |index: u32| match participant_at(env, session_id, index) {
+        Ok(address) => holds_min_balance(env, gate, &address),
+        Err(_) => false,
+    };
+    let mut winners: Vec<u32> = Vec::new(env);
+    for drawn_index in drawn.iter() {
+        let mut winner = drawn_index;
+        if !holds(drawn_index) {
+            for step in 1..=MAX_GATE_PROBES.min(n - 1) {
+                let candidate = (drawn_index + step) % n;
+                let eligible = !only_revealed_nonces(session)
+                    || env.storage().temporary().has(&DataKey::NonceRevealed(session_id, candidate));
+                if !eligible || drawn.contains(candidate) || winners.contains(candidate) {
+                    continue;
+                }
+                if holds(candidate) {
+                    winner = candidate;
+                    WinnerReplacedEvent { session_id, drawn_index, winner_index: winner }.publish(env);
+                    break;
+                }
+            }
+        }
+        winners.push_back(winner);
+    }
+    winners
+}
+
+// ─── Game Hub ───────────────────────────────────────────────────────────────
+//
+//  Una partida del Hub por ganador:
+//  game_id   = este contrato (el juego que llama al hub)
+//  player1   = streamer   (organizador)
+//  player2   = ganador k  (determinado por el ZK commit-reveal)
+//
+//  player1_points = num_participants (datos del sorteo)
+//  player2_points = winner_index     (para auditoría)
+//
+//  end_game(player1_won=false) porque el ganador es player2.
+//  El ganador 0 usa `session_id`; el resto, IDs consecutivos de un
+//  contador propio desde `HUB_SESSION_ID_START` (ver `hub_session_id`).
+
+/// Envía al Hub las llamadas pendientes de una sesión revelada, desde
+/// `hub_calls_done`, y guarda el progreso. Las llamadas `try_` aíslan los
+/// fallos del Hub (trap, contrato inexistente, ...): la primera que falla
+/// deja la sesión en `Pending` sin revertir nada de lo anterior.
+fn report_to_hub(
+    env: &Env,
+    session_id: u32,
+    session: &mut DrawSession,
+    winner_indices: &Vec<u32>,
+    winner_addresses: &Vec<Address>,
+) -> Result<(), RouletteError> {
+    let game_hub_addr: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::GameHub)
+        .ok_or(RouletteError::NotInitialized)?;
+    let game_hub = GameHubClient::new(env, &game_hub_addr);
+
+    if winner_indices.len() > 1 && session.hub_first_id == 0 {
+        // Sin IDs libres los ganadores k ≥ 1 quedan pendientes, como con el Hub caído
+        session.hub_first_id = allocate_hub_session_ids(env, winner_indices.len() - 1).unwrap_or(0);
+    }
+
+    let total_calls = winner_indices.len() * 2;
+    while session.hub_calls_done < total_calls {
+        let k = session.hub_calls_done / 2;
+        if k > 0 && session.hub_first_id == 0 {
+            break;
+        }
+        let hub_session = hub_session_id(session_id, session, k);
+        let accepted = if session.hub_calls_done.is_multiple_of(2) {
+            game_hub
+                .try_start_game(
+                    &env.current_contract_address(),
+                    &hub_session,
+                    &session.streamer,
+                    &winner_addresses.get(k).unwrap(),
+                    &(session.num_participants as i128),
+                    &(winner_indices.get(k).unwrap() as i128),
+                )
+                .is_ok()
+        } else {
+            // player2 (el ganador) es quien "gana" desde la perspectiva del Hub
+            game_hub.try_end_game(&hub_session, &false).is_ok()
+        };
+        if !accepted {
+            break;
+        }
+        session.hub_calls_done += 1;
+    }
+
+    session.hub_status = if session.hub_calls_done == total_calls {
+        HubStatus::Reported
+    } else {
+        HubStatus::Pending
+    };
+    save_session(env, session_id, session);
+    if session.hub_status == HubStatus::Pending {
+        // Margen completo para reintentar
+        env.storage().temporary().extend_ttl(&DataKey::Session(session_id), TTL_LEDGERS, TTL_LEDGERS);
+    }
+
+    HubReportEvent {
+        session_id,
+        status: session.hub_status,
+        hub_calls_done: session.hub_calls_done,
+    }
+    .publish(env);
+
+    Ok(())
+}
+
+/// Reserva `count` IDs de partida consecutivos del rango del Hub y
+/// retorna el primero; `None` si el rango no da para tantos
+fn allocate_hub_session_ids(env: &Env, count: u32) -> Option<u32> {
+    let first: u32 = env
+        .storage()
+        .instance()
+        .get(&DataKey::NextHubSessionId)
+        .unwrap_or(HUB_SESSION_ID_START);
+    let next = first.checked_add(count)?;
+    env.storage().instance().set(&DataKey::NextHubSessionId, &next);
+    Some(first)
+}
+
+/// ID de sesión en el Game Hub para el ganador `k` de una sesión.
+/// El ganador 0 conserva `session_id`; el resto usa los IDs reservados
+/// en `hub_first_id`, que se guardan con la sesión para que un
+/// reintento repita los mismos.
+fn hub_session_id(session_id: u32, session: &DrawSession, k: u32) -> u32 {
+    if k == 0 {
+        return session_id;
+    }
+    session.hub_first_id + k - 1
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+//  TESTS
+//  Ejecutar con: cargo test
+// ═══════════════════════════════════════════════════════════════════════════
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger},
+        xdr::ToXdr,
+        token::{StellarAssetClient, TokenClient},
+        symbol_short, Address, Env,
+    };
+
+    /// Game Hub mínimo para tests: acepta start_game / end_game sin efectos
+    #[contract]
+    pub struct MockGameHub;
+
+    #[contractimpl]
+    impl MockGameHub {
+        pub fn start_game(
+            _env: Env,
+            _game_id: Address,
+            _session_id: u32,
+            _player1: Address,
+            _player2: Address,
+            _player1_points: i128,
+            _player2_points: i128,
+        ) {
+        }
+
+        pub fn end_game(_env: Env, _session_id: u32, _player1_won: bool) {}
+    }
+
+    /// Game Hub que falla (trap) mientras esté caído; cuenta los
+    /// start_game aceptados
+    #[contract]
+    pub struct FlakyGameHub;
+
+    #[contractimpl]
+    impl FlakyGameHub {
+        pub fn set_down(env: Env, start_down: bool, end_down: bool) {
+            env.storage().instance().set(&symbol_short!("start_dn"), &start_down);
+            env.storage().instance().set(&symbol_short!("end_dn"), &end_down);
+        }
+
+        pub fn starts(env: Env) -> u32 {
+            env.storage().instance().get(&symbol_short!("starts")).unwrap_or(0)
+        }
+
+        pub fn start_game(
+            env: Env,
+            _game_id: Address,
+            _session_id: u32,
+            _player1: Address,
+            _player2: Address,
+            _player1_points: i128,
+            _player2_points: i128,
+        ) {
+            if env.storage().instance().get(&symbol_short!("start_dn")).unwrap_or(false) {
+                panic!("hub caído");
+            }
+            let starts = Self::starts(env.clone()) + 1;
+            env.storage().instance().set(&symbol_short!("starts"), &starts);
+        }
+
+        pub fn end_game(env: Env, _session_id: u32, _player1_won: bool) {
+            if env.storage().instance().get(&symbol_short!("end_dn")).unwrap_or(false) {
+                panic!("hub caído");
+            }
+        }
+    }
+
+    /// Construye el commitment SHA-256(secret_be8 || salt32) en Rust para tests
+    fn make_commitment(env: &Env, secret: u64, salt: &[u8; 32]) -> BytesN<32> {
+        let mut preimage = Bytes::new(env);
+        for b in secret.to_be_bytes().iter() {
+            preimage.push_back(*b);
+        }
+        for b in salt.iter() {
             preimage.push_back(*b);
         }
+        env.crypto().sha256(&preimage).into()
+    }
+
+    /// Helper: despliega el contrato y lo inicializa con un mock de Game Hub
+    fn setup() -> (Env, Address, Address) {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register(ZkRouletteContract, ());
+        let game_hub = env.register(MockGameHub, ());
+
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        client.initialize(&Address::generate(&env), &game_hub);
+
+        (env, contract_id, game_hub)
+    }
+
+    /// Helper: crea un token SAC de pruebas y acuña `amount` a `to`
+    fn create_token(env: &Env, to: &Address, amount: i128) -> Address {
+        let issuer = Address::generate(env);
+        let sac = env.register_stellar_asset_contract_v2(issuer);
+        StellarAssetClient::new(env, &sac.address()).mint(to, &amount);
+        sac.address()
+    }
+
+    /// Helper: sorteo sin entrada de pago
+    fn no_fee(token: &Address) -> EntryFee {
+        EntryFee { token: token.clone(), amount: 0, streamer_cut_bps: 0, protocol_fee_bps: 0 }
+    }
+
+    /// Helper: sorteo sin requisito de saldo
+    fn no_gate(token: &Address) -> BalanceGate {
+        BalanceGate { token: token.clone(), min_balance: 0, recheck_at_reveal: false }
+    }
+
+    /// Helper: configuración por defecto (1 ganador, sin fianza, entrada
+    /// ni requisitos, cierre manual)
+    fn default_config(token: &Address) -> SessionConfig {
+        SessionConfig {
+            num_winners: 1,
+            min_participants: 2,
+            max_participants: MAX_PARTICIPANTS,
+            registration_deadline: 0,
+            allow_early_close: true,
+            reveal_window_ledgers: DEFAULT_REVEAL_WINDOW_LEDGERS,
+            nonce_window_ledgers: DEFAULT_NONCE_WINDOW_LEDGERS,
+            bond: no_bond(token),
+            entry_fee: no_fee(token),
+            multi_party: false,
+            allowlist_root: None,
+            balance_gate: no_gate(token),
+            report_to_hub: true,
+        }
+    }
+
+    /// Helper: fianza vacía (sorteo sin fianza)
+    fn no_bond(token: &Address) -> StreamerBond {
+        StreamerBond { token: token.clone(), amount: 0, slash_recipient: None }
+    }
+
+    #[test]
+    fn test_flujo_completo() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+
+        let streamer = Address::generate(&env);
+        let p1 = Address::generate(&env);
+        let p2 = Address::generate(&env);
+        let p3 = Address::generate(&env);
+        let token = create_token(&env, &streamer, 1_000);
+        let token_client = TokenClient::new(&env, &token);
+
+        let secret: u64 = 13;
+        let salt: [u8; 32] = [0x1a; 32];
+        let commitment = make_commitment(&env, secret, &salt);
+        let salt_bn: BytesN<32> = BytesN::from_array(&env, &salt);
+
+        // Fase 1: Commit (el premio queda custodiado en el contrato)
+        client.commit_draw(&streamer, &42u32, &commitment, &CommitmentScheme::Sha256, &token, &500, &default_config(&token));
+        assert_eq!(token_client.balance(&streamer), 500);
+        assert_eq!(token_client.balance(&contract_id), 500);
+
+        // Fase 2: Registro
+        client.register_participant(&42u32, &p1, &None, &None);
+        client.register_participant(&42u32, &p2, &None, &None);
+        client.register_participant(&42u32, &p3, &None, &None);
+
+        assert_eq!(client.participant_count(&42u32), 3);
+
+        // Cerrar inscripciones
+        client.close_registrations(&42u32);
+
+        // Fase 3: Reveal → ganador derivado de (secret, salt, close_entropy)
+        client.reveal_winner(&42u32, &secret, &salt_bn);
+
+        let session = client.get_session(&42u32);
+        assert!(session.phase == DrawPhase::Revealed);
+        let expected =
+            selection::derive_winners(&env, secret, &salt_bn, &session.close_entropy, 3, 1);
+        assert_eq!(session.winner_indices, expected);
+        let players = soroban_sdk::vec![&env, p1, p2, p3];
+        let winner = players.get(expected.get(0).unwrap()).unwrap();
+        assert_eq!(token_client.balance(&winner), 500);
+        assert_eq!(token_client.balance(&contract_id), 0);
+    }
+
+    #[test]
+    fn test_flujo_con_commitment_poseidon() {
+        let (env, contract_id, _) = setup();
+        env.cost_estimate().budget().reset_unlimited();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+
+        // Vector de test_sorteo_basico en circuits/src/main.nr:
+        // Poseidon(13, 0x1a2b3c4d5e6f)
+        let mut salt = [0u8; 32];
+        salt[26..].copy_from_slice(&[0x1a, 0x2b, 0x3c, 0x4d, 0x5e, 0x6f]);
+        let salt_bn = BytesN::from_array(&env, &salt);
+        let commitment = BytesN::from_array(
+            &env,
+            &[
+                0x2e, 0x48, 0xff, 0x5a, 0xea, 0x87, 0x25, 0xd2, 0x06, 0xdb, 0x32, 0x79, 0xf9,
+                0x27, 0xc2, 0x55, 0x96, 0xc8, 0x60, 0x28, 0xdd, 0x6d, 0xd1, 0x2d, 0xc5, 0x92,
+                0xf1, 0x47, 0xcd, 0x4d, 0x98, 0xd9,
+            ],
+        );
+        client.commit_draw(&streamer, &9u32, &commitment, &CommitmentScheme::Poseidon, &token, &0, &default_config(&token));
+        for _ in 0..5 {
+            client.register_participant(&9u32, &Address::generate(&env), &None, &None);
+        }
+        client.close_registrations(&9u32);
+        client.reveal_winner(&9u32, &13u64, &salt_bn);
+
+        let session = client.get_session(&9u32);
+        assert!(session.phase == DrawPhase::Revealed);
+        assert_eq!(
+            session.winner_indices,
+            selection::derive_winners(&env, 13, &salt_bn, &session.close_entropy, 5, 1)
+        );
+    }
+
+    #[test]
+    fn test_commitment_poseidon_fuera_del_campo_falla() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+        let commitment = BytesN::from_array(&env, &[0xff; 32]);
+        assert_eq!(
+            client.try_commit_draw(&streamer, &10u32, &commitment, &CommitmentScheme::Poseidon, &token, &0, &default_config(&token)),
+            Err(Ok(RouletteError::CommitmentNotInField))
+        );
+    }
+
+    #[test]
+    fn test_sorteo_sin_premio() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let p1 = Address::generate(&env);
+        let p2 = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+
+        let salt: [u8; 32] = [0x02; 32];
+        let commitment = make_commitment(&env, 4, &salt);
+        client.commit_draw(&streamer, &3u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &default_config(&token));
+        client.register_participant(&3u32, &p1, &None, &None);
+        client.register_participant(&3u32, &p2, &None, &None);
+        client.close_registrations(&3u32);
+        let salt_bn = BytesN::from_array(&env, &salt);
+        client.reveal_winner(&3u32, &4u64, &salt_bn);
+
+        let session = client.get_session(&3u32);
+        assert_eq!(
+            session.winner_indices,
+            selection::derive_winners(&env, 4, &salt_bn, &session.close_entropy, 2, 1)
+        );
+    }
+
+    #[test]
+    fn test_multiples_ganadores() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 1_000);
+        let token_client = TokenClient::new(&env, &token);
+
+        let secret: u64 = 0x5eed_1234_abcd;
+        let salt: [u8; 32] = [0x33; 32];
+        let commitment = make_commitment(&env, secret, &salt);
+        client.commit_draw(&streamer, &8u32, &commitment, &CommitmentScheme::Sha256, &token, &1_000, &SessionConfig { num_winners: 3, ..default_config(&token) });
+
+        let mut players = Vec::new(&env);
+        for _ in 0..5 {
+            let p = Address::generate(&env);
+            client.register_participant(&8u32, &p, &None, &None);
+            players.push_back(p);
+        }
+        client.close_registrations(&8u32);
+        let salt_bn = BytesN::from_array(&env, &salt);
+        client.reveal_winner(&8u32, &secret, &salt_bn);
+
+        let session = client.get_session(&8u32);
+        let winners = session.winner_indices;
+        assert_eq!(winners.len(), 3);
+        // Sin reemplazo: índices distintos y dentro de rango
+        for a in 0..winners.len() {
+            assert!(winners.get(a).unwrap() < 5);
+            for b in (a + 1)..winners.len() {
+                assert_ne!(winners.get(a).unwrap(), winners.get(b).unwrap());
+            }
+        }
+        // Determinismo: misma derivación off-chain
+        assert_eq!(
+            winners,
+            selection::derive_winners(&env, secret, &salt_bn, &session.close_entropy, 5, 3)
+        );
+
+        // 1000 / 3 = 333 cada uno; el resto (1) va al primer ganador
+        let first = players.get(winners.get(0).unwrap()).unwrap();
+        assert_eq!(token_client.balance(&first), 334);
+        for k in 1..3 {
+            let p = players.get(winners.get(k).unwrap()).unwrap();
+            assert_eq!(token_client.balance(&p), 333);
+        }
+        assert_eq!(token_client.balance(&contract_id), 0);
+    }
+
+    #[test]
+    fn test_derivacion_cubre_todos_los_indices() {
+        let env = Env::default();
+        // Con num_winners == num_participants la salida es una permutación
+        let salt = BytesN::from_array(&env, &[0x07; 32]);
+        let winners = selection::derive_winners(&env, 987_654_321, &salt, &salt, 10, 10);
+        let mut seen = [false; 10];
+        for idx in winners.iter() {
+            assert!(!seen[idx as usize]);
+            seen[idx as usize] = true;
+        }
+        assert!(seen.iter().all(|s| *s));
+    }
+
+    #[test]
+    fn test_cierre_con_menos_participantes_que_ganadores() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+        let commitment = make_commitment(&env, 1, &[0x00; 32]);
+
+        client.commit_draw(&streamer, &5u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &SessionConfig { num_winners: 3, ..default_config(&token) });
+        client.register_participant(&5u32, &Address::generate(&env), &None, &None);
+        client.register_participant(&5u32, &Address::generate(&env), &None, &None);
+        // 2 participantes < 3 ganadores
+        assert_eq!(
+            client.try_close_registrations(&5u32),
+            Err(Ok(RouletteError::FewerParticipantsThanWinners))
+        );
+    }
+
+    /// Helper: sesión con 2 participantes ya cerrada, lista para reveal
+    fn closed_session(
+        env: &Env,
+        client: &ZkRouletteContractClient,
+        session_id: u32,
+        prize: i128,
+        bond: i128,
+        slash_recipient: Option<Address>,
+    ) -> (Address, Address, [u8; 32]) {
+        let streamer = Address::generate(env);
+        let token = create_token(env, &streamer, prize + bond);
+        let salt: [u8; 32] = [0x44; 32];
+        let commitment = make_commitment(env, 9, &salt);
+        let bond = StreamerBond { token: token.clone(), amount: bond, slash_recipient };
+        client.commit_draw(&streamer, &session_id, &commitment, &CommitmentScheme::Sha256, &token, &prize, &SessionConfig { bond: bond.clone(), ..default_config(&token) });
+        client.register_participant(&session_id, &Address::generate(env), &None, &None);
+        client.register_participant(&session_id, &Address::generate(env), &None, &None);
+        client.close_registrations(&session_id);
+        (streamer, token, salt)
+    }
+
+    #[test]
+    fn test_registro_archivado_tras_el_reveal() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let (streamer, token, salt) = closed_session(&env, &client, 60, 100, 0, None);
+        let salt_bn = BytesN::from_array(&env, &salt);
+        assert_eq!(
+            client.try_get_draw_record(&60u32).err(),
+            Some(Ok(RouletteError::DrawRecordNotFound))
+        );
+
+        env.ledger().with_mut(|l| l.sequence_number += 10);
+        client.reveal_winner(&60u32, &9u64, &salt_bn);
+        let session = client.get_session(&60u32);
+        assert_eq!((session.hub_status, session.hub_calls_done), (HubStatus::Reported, 2));
+        let participants = client.get_participants(&60u32);
+
+        // La sesión temporal expira; el registro sigue disponible
+        env.as_contract(&contract_id, || {
+            env.storage().temporary().remove(&DataKey::Session(60));
+            for index in 0..2 {
+                env.storage().temporary().remove(&DataKey::Participant(60, index));
+            }
+        });
+        assert_eq!(
+            client.try_get_session(&60u32).err(),
+            Some(Ok(RouletteError::SessionNotFound))
+        );
+
+        let record = client.get_draw_record(&60u32);
+        assert_eq!(record.streamer, streamer);
+        assert_eq!(record.commitment, session.commitment);
+        assert_eq!(record.secret_number, 9);
+        assert_eq!(record.salt, Some(salt_bn));
+        assert_eq!(record.participants_hash, session.participants_hash);
+        assert_eq!(record.draw_entropy, session.close_entropy);
+        assert_eq!(record.winner_indices, session.winner_indices);
+        let winner = participants.get(record.winner_indices.get(0).unwrap()).unwrap();
+        assert_eq!(record.winner_addresses, soroban_sdk::vec![&env, winner]);
+        assert_eq!(record.prize_token, token);
+        assert_eq!(record.prize_amount, 100);
+        assert_eq!(record.revealed_ledger, env.ledger().sequence());
+
+        assert_eq!(client.get_draw_count(&streamer), 1);
+        assert_eq!(client.get_draw_history(&streamer, &0, &10), soroban_sdk::vec![&env, 60u32]);
+        assert_eq!(client.get_draw_history(&streamer, &1, &10).len(), 0);
+
+        // El ID archivado no puede reutilizarse
+        let commitment = make_commitment(&env, 1, &[0; 32]);
+        assert_eq!(
+            client.try_commit_draw(&streamer, &60u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &default_config(&token)),
+            Err(Ok(RouletteError::SessionAlreadyExists))
+        );
+    }
+
+    #[test]
+    fn test_historial_paginado_por_streamer() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+        let salt = [0x21; 32];
+        let commitment = make_commitment(&env, 5, &salt);
+
+        for id in [3u32, 1, 2] {
+            client.commit_draw(&streamer, &id, &commitment, &CommitmentScheme::Sha256, &token, &0, &default_config(&token));
+            client.register_participant(&id, &Address::generate(&env), &None, &None);
+            client.register_participant(&id, &Address::generate(&env), &None, &None);
+            client.close_registrations(&id);
+            client.reveal_winner(&id, &5u64, &BytesN::from_array(&env, &salt));
+        }
+
+        // Orden de reveal, no de ID
+        assert_eq!(client.get_draw_count(&streamer), 3);
+        assert_eq!(client.get_draw_history(&streamer, &0, &2), soroban_sdk::vec![&env, 3u32, 1]);
+        assert_eq!(client.get_draw_history(&streamer, &2, &2), soroban_sdk::vec![&env, 2u32]);
+        assert_eq!(client.get_draw_count(&Address::generate(&env)), 0);
+    }
+
+    #[test]
+    fn test_expirar_sorteo_reembolsa_premio() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let (streamer, token, _) = closed_session(&env, &client, 11, 300, 0, None);
+        let token_client = TokenClient::new(&env, &token);
+        assert_eq!(token_client.balance(&contract_id), 300);
+
+        let deadline = client.get_session(&11u32).reveal_deadline;
+        env.ledger().with_mut(|l| l.sequence_number = deadline + 1);
+
+        client.expire_draw(&11u32);
+
+        assert!(client.get_session(&11u32).phase == DrawPhase::Expired);
+        assert_eq!(token_client.balance(&streamer), 300);
+        assert_eq!(token_client.balance(&contract_id), 0);
+    }
+
+    #[test]
+    fn test_fianza_devuelta_en_reveal() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let (streamer, token, salt) = closed_session(&env, &client, 14, 100, 50, None);
+        let token_client = TokenClient::new(&env, &token);
+
+        assert_eq!(client.get_session_config(&14u32).bond.amount, 50);
+        assert_eq!(client.get_outstanding_bonds(&streamer).get(token.clone()), Some(50));
+        assert_eq!(client.get_bonded_sessions(&streamer), soroban_sdk::vec![&env, 14u32]);
+
+        client.reveal_winner(&14u32, &9u64, &BytesN::from_array(&env, &salt));
+
+        assert_eq!(token_client.balance(&streamer), 50);
+        assert_eq!(token_client.balance(&contract_id), 0);
+        assert!(client.get_outstanding_bonds(&streamer).is_empty());
+        assert!(client.get_bonded_sessions(&streamer).is_empty());
+    }
+
+    #[test]
+    fn test_fianza_repartida_entre_participantes_al_expirar() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let (streamer, token, _) = closed_session(&env, &client, 15, 0, 101, None);
+        let token_client = TokenClient::new(&env, &token);
+
+        let deadline = client.get_session(&15u32).reveal_deadline;
+        env.ledger().with_mut(|l| l.sequence_number = deadline + 1);
+        client.expire_draw(&15u32);
+
+        // 101 / 2 = 50; el resto (1) va al participante 0
+        let participants = client.get_participants(&15u32);
+        let (p0, p1) = (participants.get(0).unwrap(), participants.get(1).unwrap());
+        client.claim_bond_share(&15u32, &p0);
+        client.claim_bond_share(&15u32, &p1);
+        assert_eq!(token_client.balance(&p0), 51);
+        assert_eq!(token_client.balance(&p1), 50);
+        assert_eq!(token_client.balance(&contract_id), 0);
+        assert_eq!(token_client.balance(&streamer), 0);
+        assert!(client.get_outstanding_bonds(&streamer).is_empty());
+
+        assert_eq!(
+            client.try_claim_bond_share(&15u32, &p0),
+            Err(Ok(RouletteError::BondShareAlreadyClaimed))
+        );
+        assert_eq!(
+            client.try_claim_bond_share(&15u32, &streamer),
+            Err(Ok(RouletteError::NotRegistered))
+        );
+    }
+
+    #[test]
+    fn test_fianza_enviada_a_destino_configurado_al_expirar() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let treasury = Address::generate(&env);
+        let (streamer, token, _) =
+            closed_session(&env, &client, 16, 20, 80, Some(treasury.clone()));
+        let token_client = TokenClient::new(&env, &token);
+
+        let deadline = client.get_session(&16u32).reveal_deadline;
+        env.ledger().with_mut(|l| l.sequence_number = deadline + 1);
+        client.expire_draw(&16u32);
+
+        // El premio vuelve al streamer; la fianza va al destino configurado
+        assert_eq!(token_client.balance(&streamer), 20);
+        assert_eq!(token_client.balance(&treasury), 80);
+        assert!(client.get_bonded_sessions(&streamer).is_empty());
+    }
+
+    #[test]
+    fn test_expirar_antes_del_plazo_falla() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        closed_session(&env, &client, 12, 0, 0, None);
+
+        let deadline = client.get_session(&12u32).reveal_deadline;
+        env.ledger().with_mut(|l| l.sequence_number = deadline);
+        assert_eq!(
+            client.try_expire_draw(&12u32),
+            Err(Ok(RouletteError::RevealDeadlineNotReached))
+        );
+    }
+
+    #[test]
+    fn test_reveal_tras_el_plazo_falla() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let (_, _, salt) = closed_session(&env, &client, 13, 0, 0, None);
+
+        let deadline = client.get_session(&13u32).reveal_deadline;
+        env.ledger().with_mut(|l| l.sequence_number = deadline + 1);
+        assert_eq!(
+            client.try_reveal_winner(&13u32, &9u64, &BytesN::from_array(&env, &salt)),
+            Err(Ok(RouletteError::RevealDeadlinePassed))
+        );
+    }
+
+    #[test]
+    fn test_hash_de_participantes_encadenado() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        closed_session(&env, &client, 20, 0, 0, None);
+
+        let session = client.get_session(&20u32);
+        let mut expected = BytesN::from_array(&env, &[0u8; 32]);
+        for addr in client.get_participants(&20u32).iter() {
+            let mut link = Bytes::from_array(&env, &expected.to_array());
+            link.append(&addr.to_xdr(&env));
+            expected = env.crypto().sha256(&link).into();
+        }
+        assert_eq!(session.participants_hash, expected);
+        assert_ne!(session.close_entropy, BytesN::from_array(&env, &[0u8; 32]));
+    }
+
+    #[test]
+    fn test_entropia_de_cierre_depende_del_ledger() {
+        // Mismo secreto, salt y participantes; sólo cambia el ledger de cierre
+        let mut entropies = [[0u8; 32]; 2];
+        for (i, sequence) in [1_000u32, 1_001].into_iter().enumerate() {
+            let (env, contract_id, _) = setup();
+            let client = ZkRouletteContractClient::new(&env, &contract_id);
+            env.ledger().with_mut(|l| l.sequence_number = sequence);
+            closed_session(&env, &client, 21, 0, 0, None);
+            entropies[i] = client.get_session(&21u32).close_entropy.to_array();
+        }
+        assert_ne!(entropies[0], entropies[1]);
+    }
+
+    #[test]
+    fn test_ataque_de_direccion_con_cuentas_propias_falla() {
+        // El streamer conoce (secret, salt) y quiere que gane su cuenta
+        // `puppet`. Con el esquema anterior bastaba con añadir cuentas de
+        // relleno hasta que la predicción cayera en `puppet`. Ahora su mejor
+        // predicción (sin conocer la entropía de cierre) no sirve: acierta
+        // aproximadamente 1/n de las veces, no siempre.
+        let (env, contract_id, _) = setup();
+        env.cost_estimate().budget().reset_unlimited();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+        let salt: [u8; 32] = [0x66; 32];
+        let salt_bn = BytesN::from_array(&env, &salt);
+        let unknown = BytesN::from_array(&env, &[0u8; 32]);
+
+        let attempts: u32 = 40;
+        let mut hits = 0;
+        for session_id in 100..100 + attempts {
+            let secret = 1_000 + session_id as u64;
+            let commitment = make_commitment(&env, secret, &salt);
+            let bond = no_bond(&token);
+            client.commit_draw(&streamer, &session_id, &commitment, &CommitmentScheme::Sha256, &token, &0, &SessionConfig { bond: bond.clone(), ..default_config(&token) });
+
+            for _ in 0..3 {
+                client.register_participant(&session_id, &Address::generate(&env), &None, &None);
+            }
+            let puppet = Address::generate(&env);
+            client.register_participant(&session_id, &puppet, &None, &None);
+            let puppet_index = 3;
+
+            // Añadir relleno hasta que la predicción del streamer elija `puppet`
+            for _ in 0..32 {
+                let n = client.participant_count(&session_id);
+                let predicted = selection::derive_winners(&env, secret, &salt_bn, &unknown, n, 1);
+                if predicted.get(0).unwrap() == puppet_index {
+                    break;
+                }
+                client.register_participant(&session_id, &Address::generate(&env), &None, &None);
+            }
+
+            client.close_registrations(&session_id);
+            client.reveal_winner(&session_id, &secret, &salt_bn);
+            if client.get_session(&session_id).winner_indices.get(0).unwrap() == puppet_index {
+                hits += 1;
+            }
+        }
+
+        // Sin entropía de cierre el ataque acertaría 40/40
+        assert!(hits < attempts / 2, "steering succeeded {} / {}", hits, attempts);
+    }
+
+    /// Helper: compromiso de nonce SHA-256(nonce || xdr(participant))
+    fn nonce_commitment(env: &Env, nonce: &BytesN<32>, participant: &Address) -> BytesN<32> {
+        let mut preimage = Bytes::from_array(env, &nonce.to_array());
+        preimage.append(&participant.clone().to_xdr(env));
+        env.crypto().sha256(&preimage).into()
+    }
+
+    /// Helper: sesión multi-party con 3 participantes y sus nonces
+    fn multi_party_session(
+        env: &Env,
+        client: &ZkRouletteContractClient,
+        session_id: u32,
+    ) -> (soroban_sdk::Vec<Address>, soroban_sdk::Vec<BytesN<32>>, [u8; 32]) {
+        let streamer = Address::generate(env);
+        let token = create_token(env, &streamer, 0);
+        let salt: [u8; 32] = [0x77; 32];
+        let commitment = make_commitment(env, 21, &salt);
+        client.commit_draw(&streamer, &session_id, &commitment, &CommitmentScheme::Sha256, &token, &0, &SessionConfig { multi_party: true, ..default_config(&token) });
+
+        let mut players = Vec::new(env);
+        let mut nonces = Vec::new(env);
+        for i in 0..3u8 {
+            let p = Address::generate(env);
+            let nonce = BytesN::from_array(env, &[i + 1; 32]);
+            client.register_participant(&session_id, &p, &Some(nonce_commitment(env, &nonce, &p)), &None);
+            players.push_back(p);
+            nonces.push_back(nonce);
+        }
+        client.close_registrations(&session_id);
+        (players, nonces, salt)
+    }
+
+    #[test]
+    fn test_multi_party_excluye_a_quien_no_revela() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let (players, nonces, salt) = multi_party_session(&env, &client, 30);
+
+        // Revelan los participantes 0 y 2; el 1 se calla
+        client.reveal_nonce(&30u32, &players.get(0).unwrap(), &nonces.get(0).unwrap());
+        client.reveal_nonce(&30u32, &players.get(2).unwrap(), &nonces.get(2).unwrap());
+
+        let session = client.get_session(&30u32);
+        assert_eq!(session.nonces_revealed, 2);
+        // XOR de [1; 32] y [3; 32] = [2; 32]
+        assert_eq!(session.participant_entropy, BytesN::from_array(&env, &[2u8; 32]));
+
+        env.ledger().with_mut(|l| l.sequence_number = session.nonce_deadline + 1);
+        let salt_bn = BytesN::from_array(&env, &salt);
+        client.reveal_winner(&30u32, &21u64, &salt_bn);
+
+        // Reproducción off-chain: extracción sobre los elegibles [0, 2]
+        let session = client.get_session(&30u32);
+        let mut preimage = Bytes::from_array(&env, &session.close_entropy.to_array());
+        preimage.extend_from_array(&[2u8; 32]);
+        let entropy: BytesN<32> = env.crypto().sha256(&preimage).into();
+        let drawn = selection::derive_winners(&env, 21, &salt_bn, &entropy, 2, 1);
+        let expected = [0u32, 2][drawn.get(0).unwrap() as usize];
+        assert_eq!(session.winner_indices, soroban_sdk::vec![&env, expected]);
+        assert_ne!(session.winner_indices.get(0).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_multi_party_sin_nonces_suficientes_cancela() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 150);
+        let token_client = TokenClient::new(&env, &token);
+        let salt = [0x78; 32];
+        let config = SessionConfig {
+            multi_party: true,
+            num_winners: 2,
+            bond: StreamerBond { token: token.clone(), amount: 50, slash_recipient: None },
+            ..default_config(&token)
+        };
+        client.commit_draw(&streamer, &34u32, &make_commitment(&env, 21, &salt), &CommitmentScheme::Sha256, &token, &100, &config);
+        let mut players = Vec::new(&env);
+        for i in 0..3u8 {
+            let p = Address::generate(&env);
+            let nonce = BytesN::from_array(&env, &[i + 1; 32]);
+            client.register_participant(&34u32, &p, &Some(nonce_commitment(&env, &nonce, &p)), &None);
+            players.push_back((p, nonce));
+        }
+        client.close_registrations(&34u32);
+
+        // Sólo revela uno: no hay pool para dos ganadores y los demás no entran
+        let (p0, n0) = players.get(0).unwrap();
+        client.reveal_nonce(&34u32, &p0, &n0);
+        assert_eq!(client.try_expire_draw(&34u32), Err(Ok(RouletteError::RevealDeadlineNotReached)));
+        let session = client.get_session(&34u32);
+        env.ledger().with_mut(|l| l.sequence_number = session.nonce_deadline + 1);
+        assert_eq!(
+            client.try_reveal_winner(&34u32, &21u64, &BytesN::from_array(&env, &salt)),
+            Err(Ok(RouletteError::NotEnoughNonces))
+        );
+
+        // Cualquiera la cancela sin esperar al plazo de reveal; no se confisca nada
+        client.expire_draw(&34u32);
+        assert!(client.get_session(&34u32).phase == DrawPhase::Cancelled);
+        assert_eq!(token_client.balance(&streamer), 150);
+        assert_eq!(client.get_late_cancellations(&streamer), 0);
+        assert_eq!(client.try_claim_bond_share(&34u32, &p0), Err(Ok(RouletteError::SessionNotExpired)));
+    }
+
+    #[test]
+    fn test_multi_party_nonce_incorrecto_falla() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let (players, nonces, _) = multi_party_session(&env, &client, 31);
+
+        // El participante 0 intenta revelar el nonce del participante 1
+        assert_eq!(
+            client.try_reveal_nonce(&31u32, &players.get(0).unwrap(), &nonces.get(1).unwrap()),
+            Err(Ok(RouletteError::NonceCommitmentMismatch))
+        );
+    }
+
+    #[test]
+    fn test_multi_party_sin_compromiso_guardado_falla() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let (players, nonces, _) = multi_party_session(&env, &client, 33);
+
+        // La entrada del compromiso caducó antes que la sesión
+        env.as_contract(&contract_id, || {
+            env.storage().temporary().remove(&DataKey::NonceCommitment(33, 0));
+        });
+        assert_eq!(
+            client.try_reveal_nonce(&33u32, &players.get(0).unwrap(), &nonces.get(0).unwrap()),
+            Err(Ok(RouletteError::NonceCommitmentNotFound))
+        );
+        assert_eq!(
+            client.try_reveal_nonce(&33u32, &Address::generate(&env), &nonces.get(0).unwrap()),
+            Err(Ok(RouletteError::NotRegistered))
+        );
+    }
+
+    #[test]
+    fn test_multi_party_reveal_antes_de_cerrar_ventana_falla() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let (players, nonces, salt) = multi_party_session(&env, &client, 32);
+
+        client.reveal_nonce(&32u32, &players.get(0).unwrap(), &nonces.get(0).unwrap());
+        assert_eq!(
+            client.try_reveal_winner(&32u32, &21u64, &BytesN::from_array(&env, &salt)),
+            Err(Ok(RouletteError::NonceWindowOpen))
+        );
+    }
+
+    #[test]
+    fn test_multi_party_registro_sin_compromiso_falla() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+        let commitment = make_commitment(&env, 1, &[0x00; 32]);
+        client.commit_draw(&streamer, &33u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &SessionConfig { multi_party: true, ..default_config(&token) });
+        assert_eq!(
+            client.try_register_participant(&33u32, &Address::generate(&env), &None, &None),
+            Err(Ok(RouletteError::NonceCommitmentRequired))
+        );
+    }
+
+    /// Helper: sesión cerrada con commitment Poseidon (simulado) y 3
+    /// participantes; devuelve las entradas públicas para `winner_index`
+    fn proof_session(
+        env: &Env,
+        client: &ZkRouletteContractClient,
+        session_id: u32,
+        winner_index: u32,
+    ) -> (soroban_sdk::Vec<Address>, Address, soroban_sdk::Vec<BytesN<32>>) {
+        let streamer = Address::generate(env);
+        let token = create_token(env, &streamer, 300);
+        // Un elemento de Fr cualquiera hace de Poseidon(secret, salt):
+        // la prueba sintética no depende del preimage
+        let mut commitment = [0x0b; 32];
+        commitment[0] = 0;
+        let commitment = BytesN::from_array(env, &commitment);
+        client.commit_draw(&streamer, &session_id, &commitment, &CommitmentScheme::Poseidon, &token, &300, &default_config(&token));
+
+        let mut players = Vec::new(env);
+        for _ in 0..3 {
+            let p = Address::generate(env);
+            client.register_participant(&session_id, &p, &None, &None);
+            players.push_back(p);
+        }
+        client.close_registrations(&session_id);
+
+        let session = client.get_session(&session_id);
+        let mut inputs = soroban_sdk::vec![env, commitment, verifier::field_from_u32(env, 3)];
+        for b in session.close_entropy.to_array().iter() {
+            inputs.push_back(verifier::field_from_u32(env, *b as u32));
+        }
+        inputs.push_back(verifier::field_from_u32(env, winner_index));
+        (players, token, inputs)
+    }
+
+    #[test]
+    fn test_reveal_con_prueba() {
+        let (env, contract_id, _) = setup();
+        env.cost_estimate().budget().reset_unlimited();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let (players, token, inputs) = proof_session(&env, &client, 50, 2);
+
+        let (vk, proof) = verifier::test::synthetic_proof(&env, &inputs);
+        client.set_verification_key(&vk);
+        client.reveal_winner_with_proof(&50u32, &proof, &inputs);
+
+        let session = client.get_session(&50u32);
+        assert!(session.phase == DrawPhase::Revealed);
+        assert_eq!(session.winner_indices, soroban_sdk::vec![&env, 2u32]);
+        let token_client = TokenClient::new(&env, &token);
+        assert_eq!(token_client.balance(&players.get(2).unwrap()), 300);
+        assert_eq!(token_client.balance(&contract_id), 0);
+    }
+
+    #[test]
+    fn test_reveal_con_prueba_de_otro_enunciado_falla() {
+        let (env, contract_id, _) = setup();
+        env.cost_estimate().budget().reset_unlimited();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let (_, _, mut inputs) = proof_session(&env, &client, 51, 0);
+
+        // Prueba válida, pero para un sorteo con 2 participantes
+        inputs.set(1, verifier::field_from_u32(&env, 2));
+        let (vk, proof) = verifier::test::synthetic_proof(&env, &inputs);
+        client.set_verification_key(&vk);
+        assert_eq!(
+            client.try_reveal_winner_with_proof(&51u32, &proof, &inputs),
+            Err(Ok(RouletteError::ParticipantsInputMismatch))
+        );
+    }
+
+    #[test]
+    fn test_reveal_con_prueba_rechaza_boletos_sobre_el_limite_del_circuito() {
+        let (env, contract_id, _) = setup();
+        env.cost_estimate().budget().reset_unlimited();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+        let mut commitment = [0x0c; 32];
+        commitment[0] = 0;
+        let commitment = BytesN::from_array(&env, &commitment);
+        client.commit_draw(&streamer, &53u32, &commitment, &CommitmentScheme::Poseidon, &token, &0, &default_config(&token));
+        // 11 × MAX_WEIGHT boletos > 100 000
+        for _ in 0..11 {
+            let p = Address::generate(&env);
+            client.register_participant(&53u32, &p, &None, &None);
+            client.set_participant_weight(&53u32, &p, &weights::MAX_WEIGHT);
+        }
+        client.close_registrations(&53u32);
+
+        let mut inputs = Vec::new(&env);
+        for _ in 0..verifier::NUM_PUBLIC_INPUTS {
+            inputs.push_back(verifier::field_from_u32(&env, 0));
+        }
+        let (vk, proof) = verifier::test::synthetic_proof(&env, &inputs);
+        client.set_verification_key(&vk);
+        assert_eq!(
+            client.try_reveal_winner_with_proof(&53u32, &proof, &inputs),
+            Err(Ok(RouletteError::TooManyTicketsForProof))
+        );
+    }
+
+    #[test]
+    fn test_reveal_con_prueba_invalida_falla() {
+        let (env, contract_id, _) = setup();
+        env.cost_estimate().budget().reset_unlimited();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let (_, _, inputs) = proof_session(&env, &client, 52, 1);
+
+        // La prueba corresponde a otro ganador
+        let mut other = inputs.clone();
+        other.set(verifier::NUM_PUBLIC_INPUTS - 1, verifier::field_from_u32(&env, 0));
+        let (vk, proof) = verifier::test::synthetic_proof(&env, &other);
+        client.set_verification_key(&vk);
+        assert_eq!(
+            client.try_reveal_winner_with_proof(&52u32, &proof, &inputs),
+            Err(Ok(RouletteError::InvalidProof))
+        );
+    }
+
+    #[test]
+    fn test_traspaso_de_admin_en_dos_pasos() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let admin = client.get_admin();
+        let new_admin = Address::generate(&env);
+
+        client.propose_admin(&new_admin);
+        assert_eq!(env.auths()[0].0, admin);
+        // Hasta aceptar, el admin sigue siendo el anterior
+        assert_eq!(client.get_admin(), admin);
+
+        client.accept_admin();
+        assert_eq!(env.auths()[0].0, new_admin);
+        assert_eq!(client.get_admin(), new_admin);
+        // La propuesta se consume
+        assert_eq!(client.try_accept_admin(), Err(Ok(RouletteError::NoPendingAdmin)));
+    }
+
+    #[test]
+    fn test_aceptar_admin_sin_propuesta_falla() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        assert_eq!(client.try_accept_admin(), Err(Ok(RouletteError::NoPendingAdmin)));
+    }
+
+    #[test]
+    fn test_migracion_de_version() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        assert_eq!(client.get_version(), CONTRACT_VERSION);
+        assert_eq!(client.try_migrate(), Err(Ok(RouletteError::AlreadyMigrated)));
+
+        // Storage sin `DataKey::Version`
+        env.as_contract(&contract_id, || {
+            env.storage().instance().remove(&DataKey::Version);
+        });
+        assert_eq!(client.get_version(), 0);
+        client.migrate();
+        assert_eq!(client.get_version(), CONTRACT_VERSION);
+
+        // Sólo 0 → 1 es un paso vacío; cualquier otra versión exige su rama
+        env.as_contract(&contract_id, || {
+            assert_eq!(migrate_step(&env, 0), Ok(()));
+            assert_eq!(migrate_step(&env, CONTRACT_VERSION), Err(RouletteError::UnknownStorageVersion));
+        });
+    }
+
+    #[test]
+    fn test_hub_caido_no_bloquea_el_reveal() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register(ZkRouletteContract, ());
+        let hub = env.register(FlakyGameHub, ());
+        let hub_client = FlakyGameHubClient::new(&env, &hub);
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        client.initialize(&Address::generate(&env), &hub);
+        let (_, token, salt) = closed_session(&env, &client, 130, 100, 0, None);
+
+        hub_client.set_down(&true, &true);
+        client.reveal_winner(&130u32, &9u64, &BytesN::from_array(&env, &salt));
+        let session = client.get_session(&130u32);
+        assert!(session.phase == DrawPhase::Revealed);
+        assert_eq!((session.hub_status, session.hub_calls_done), (HubStatus::Pending, 0));
+        let winner = client.get_draw_record(&130u32).winner_addresses.get(0).unwrap();
+        assert_eq!(TokenClient::new(&env, &token).balance(&winner), 100);
+
+        // start_game vuelve; end_game sigue fallando
+        hub_client.set_down(&false, &true);
+        assert_eq!(client.retry_hub_report(&130u32), HubStatus::Pending);
+        assert_eq!(client.get_session(&130u32).hub_calls_done, 1);
+
+        // El reintento continúa desde end_game, sin repetir start_game
+        hub_client.set_down(&false, &false);
+        assert_eq!(client.retry_hub_report(&130u32), HubStatus::Reported);
+        assert_eq!(hub_client.starts(), 1);
+        assert_eq!(client.try_retry_hub_report(&130u32), Err(Ok(RouletteError::HubReportNotPending)));
+    }
+
+    #[test]
+    fn test_pausa_por_categoria() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let (streamer, token, salt) = closed_session(&env, &client, 120, 100, 50, None);
+        let commitment = make_commitment(&env, 3, &[0x62; 32]);
+        client.commit_draw(&streamer, &121u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &default_config(&token));
+
+        client.pause(&PauseCategory::Creation);
+        client.pause(&PauseCategory::Registration);
+        assert_eq!(env.auths()[0].0, client.get_admin());
+        assert_eq!(
+            client.get_pause_state(),
+            PauseState { creation: true, registration: true, ..Default::default() }
+        );
+        assert_eq!(
+            client.try_commit_draw(&streamer, &122u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &default_config(&token)),
+            Err(Ok(RouletteError::Paused))
+        );
+        assert_eq!(
+            client.try_register_participant(&121u32, &Address::generate(&env), &None, &None),
+            Err(Ok(RouletteError::Paused))
+        );
+        assert_eq!(
+            client.try_register_participants(&121u32, &Vec::from_array(&env, [Address::generate(&env)]), &false),
+            Err(Ok(RouletteError::Paused))
+        );
+        // La cancelación sigue disponible
+        client.cancel_draw(&121u32);
+
+        // Reveal pausado: tampoco se puede expirar
+        client.pause(&PauseCategory::Reveal);
+        assert_eq!(
+            client.try_reveal_winner(&120u32, &9u64, &BytesN::from_array(&env, &salt)),
+            Err(Ok(RouletteError::Paused))
+        );
+        let deadline = client.get_session(&120u32).reveal_deadline;
+        env.ledger().with_mut(|l| l.sequence_number = deadline + 1);
+        assert_eq!(client.try_expire_draw(&120u32), Err(Ok(RouletteError::Paused)));
+
+        // Al despausar, el plazo se ha retrasado lo que duró la pausa
+        client.unpause(&PauseCategory::Reveal);
+        client.unpause(&PauseCategory::Creation);
+        client.unpause(&PauseCategory::Registration);
+        assert_eq!(
+            client.get_pause_state(),
+            PauseState { reveal_paused_ledgers: deadline + 1, ..Default::default() }
+        );
+        assert_eq!(client.get_session(&120u32).reveal_deadline, 2 * deadline + 1);
+        assert_eq!(client.try_expire_draw(&120u32), Err(Ok(RouletteError::RevealDeadlineNotReached)));
+        env.ledger().with_mut(|l| l.sequence_number = 2 * deadline + 2);
+        client.expire_draw(&120u32);
+        client.commit_draw(&streamer, &122u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &default_config(&token));
+    }
+
+    #[test]
+    fn test_pausa_de_reveals_retrasa_el_plazo() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let (_, _, salt) = closed_session(&env, &client, 123, 100, 50, None);
+        let deadline = client.get_session(&123u32).reveal_deadline;
+
+        // Pausa de 300 ledgers a mitad de la ventana
+        env.ledger().with_mut(|l| l.sequence_number = deadline - 10);
+        client.pause(&PauseCategory::Reveal);
+        env.ledger().with_mut(|l| l.sequence_number = deadline + 290);
+        client.unpause(&PauseCategory::Reveal);
+
+        // Conserva los 10 ledgers que le quedaban: no se puede expirar
+        assert_eq!(client.get_session(&123u32).reveal_deadline, deadline + 300);
+        assert_eq!(client.try_expire_draw(&123u32), Err(Ok(RouletteError::RevealDeadlineNotReached)));
+        env.ledger().with_mut(|l| l.sequence_number = deadline + 300);
+        client.reveal_winner(&123u32, &9u64, &BytesN::from_array(&env, &salt));
+        assert!(client.get_session(&123u32).phase == DrawPhase::Revealed);
+    }
+
+    #[test]
+    fn test_pausa_larga_no_deja_caducar_la_sesion() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let (streamer, token, _) = closed_session(&env, &client, 124, 100, 50, None);
+        let token_client = TokenClient::new(&env, &token);
+        let opened_at = client.get_session(&124u32).opened_at;
+
+        // Pausa que llevaría el plazo más allá del TTL de las entradas
+        client.pause(&PauseCategory::Reveal);
+        env.ledger().with_mut(|l| l.sequence_number = opened_at + MAX_REVEAL_DEADLINE_LEDGERS);
+        assert_eq!(
+            client.get_session(&124u32).reveal_deadline,
+            opened_at + MAX_REVEAL_DEADLINE_LEDGERS
+        );
+        assert!(opened_at + MAX_REVEAL_DEADLINE_LEDGERS < TTL_LEDGERS);
+        assert_eq!(client.try_expire_draw(&124u32), Err(Ok(RouletteError::RevealDeadlineNotReached)));
+
+        // Vencido el tope se puede expirar aunque la pausa siga, antes de
+        // que caduque la sesión
+        env.ledger().with_mut(|l| l.sequence_number = opened_at + MAX_REVEAL_DEADLINE_LEDGERS + 1);
+        client.expire_draw(&124u32);
+        assert_eq!(token_client.balance(&streamer), 100);
+        assert_eq!(token_client.balance(&contract_id), 50);
+    }
+
+    #[test]
+    fn test_create_draw_asigna_ids_consecutivos() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+        let commitment = make_commitment(&env, 3, &[0x07; 32]);
+
+        // Los IDs elegidos con commit_draw no tocan el rango del contador
+        client.commit_draw(&streamer, &1u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &default_config(&token));
+        let first = client.create_draw(&streamer, &commitment, &CommitmentScheme::Sha256, &token, &0, &default_config(&token));
+        let second = client.create_draw(&streamer, &commitment, &CommitmentScheme::Sha256, &token, &0, &default_config(&token));
+
+        assert_eq!((first, second), (AUTO_SESSION_ID_START, AUTO_SESSION_ID_START + 1));
+        assert!(client.get_session(&first).streamer == streamer);
+        assert!(client.get_session(&second).phase == DrawPhase::Open);
+        // Nadie puede ocupar de antemano un ID del contador
+        assert_eq!(
+            client.try_commit_draw(&streamer, &(second + 1), &commitment, &CommitmentScheme::Sha256, &token, &0, &default_config(&token)),
+            Err(Ok(RouletteError::SessionIdReserved))
+        );
+        assert_eq!(
+            client.try_commit_draw(&streamer, &(AUTO_SESSION_ID_START - 1), &commitment, &CommitmentScheme::Sha256, &token, &0, &default_config(&token)),
+            Ok(Ok(()))
+        );
+    }
+
+    #[test]
+    fn test_contador_anterior_salta_al_rango_reservado() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+        let commitment = make_commitment(&env, 3, &[0x08; 32]);
+        // Contador de un despliegue que asignaba IDs desde 1
+        env.as_contract(&contract_id, || {
+            env.storage().instance().set(&DataKey::NextSessionId, &5u32);
+        });
+
+        let id = client.create_draw(&streamer, &commitment, &CommitmentScheme::Sha256, &token, &0, &default_config(&token));
+        assert_eq!(id, AUTO_SESSION_ID_START);
+
+        // El contador no invade el rango de partidas del Hub
+        env.as_contract(&contract_id, || {
+            env.storage().instance().set(&DataKey::NextSessionId, &HUB_SESSION_ID_START);
+        });
+        assert_eq!(
+            client.try_create_draw(&streamer, &commitment, &CommitmentScheme::Sha256, &token, &0, &default_config(&token)),
+            Err(Ok(RouletteError::SessionIdsExhausted))
+        );
+    }
+
+    #[test]
+    fn test_ids_del_hub_no_colisionan_entre_sesiones() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+        let salt = [0x5a; 32];
+        let commitment = make_commitment(&env, 11, &salt);
+
+        for (session_id, num_winners) in [(140u32, 3u32), (141, 2), (142, 1)] {
+            client.commit_draw(&streamer, &session_id, &commitment, &CommitmentScheme::Sha256, &token, &0, &SessionConfig { num_winners, ..default_config(&token) });
+            for _ in 0..3 {
+                client.register_participant(&session_id, &Address::generate(&env), &None, &None);
+            }
+            client.close_registrations(&session_id);
+            client.reveal_winner(&session_id, &11u64, &BytesN::from_array(&env, &salt));
+        }
 
-        // Agregar los 32 bytes del salt
-        let salt_arr = salt.to_array();
-        for b in salt_arr.iter() {
-            preimage.push_back(*b);
+        // Ganadores k ≥ 1: IDs consecutivos del rango del Hub, sin repetir
+        let session = |id: u32| client.get_session(&id);
+        assert_eq!(session(140).hub_first_id, HUB_SESSION_ID_START);
+        assert_eq!(session(141).hub_first_id, HUB_SESSION_ID_START + 2);
+        assert_eq!(session(142).hub_first_id, 0);
+        assert_eq!(hub_session_id(140, &session(140), 2), HUB_SESSION_ID_START + 1);
+        assert_eq!(hub_session_id(141, &session(141), 0), 141);
+        assert!(session(141).hub_status == HubStatus::Reported);
+    }
+
+    #[test]
+    fn test_registro_de_coste_constante_mas_alla_de_1024() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+        let salt = [0x3c; 32];
+        let commitment = make_commitment(&env, 77, &salt);
+        client.commit_draw(&streamer, &70u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &SessionConfig { num_winners: 2, ..default_config(&token) });
+
+        // Entradas leídas/escritas y bytes escritos: lo que cobra la red.
+        // (El presupuesto de CPU del entorno de tests crece con el storage
+        // total acumulado, no con lo que toca la invocación.)
+        let register = |p: &Address| {
+            client.register_participant(&70u32, p, &None, &None);
+            let r = env.cost_estimate().resources();
+            (r.memory_read_entries, r.write_entries, r.write_bytes)
+        };
+        let first = Address::generate(&env);
+        let first_cost = register(&first);
+        for _ in 1..1100 {
+            client.register_participant(&70u32, &Address::generate(&env), &None, &None);
         }
+        let last = Address::generate(&env);
+        let last_cost = register(&last);
+        // El coste no depende del tamaño de la lista
+        assert_eq!(first_cost, last_cost);
 
-        // Calcular hash y comparar con el compromiso inicial
-        let computed_hash: BytesN<32> = env.crypto().sha256(&preimage);
+        assert_eq!(client.participant_count(&70u32), 1101);
+        assert_eq!(client.get_participant_index(&70u32, &last), Some(1100));
+        assert_eq!(
+            client.try_register_participant(&70u32, &first, &None, &None),
+            Err(Ok(RouletteError::AlreadyRegistered))
+        );
+        let page = client.get_participants_page(&70u32, &1099, &10);
+        assert_eq!(page.len(), 2);
+        assert_eq!(page.get(1).unwrap(), last);
 
-        if computed_hash != session.commitment {
-            panic!("commitment mismatch: proof is invalid");
+        client.close_registrations(&70u32);
+        env.cost_estimate().budget().reset_unlimited();
+        client.reveal_winner(&70u32, &77u64, &BytesN::from_array(&env, &salt));
+        // El reveal sólo lee las entradas ganadoras, no las 1101
+        assert!(env.cost_estimate().resources().memory_read_entries < 20);
+        let session = client.get_session(&70u32);
+        let expected = selection::derive_winners(
+            &env, 77, &BytesN::from_array(&env, &salt), &session.close_entropy, 1101, 2,
+        );
+        assert_eq!(session.winner_indices, expected);
+    }
+
+    #[test]
+    fn test_registro_por_lotes() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+        let commitment = make_commitment(&env, 8, &[0x08; 32]);
+        client.commit_draw(&streamer, &80u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &default_config(&token));
+
+        let (a, b, c) = (Address::generate(&env), Address::generate(&env), Address::generate(&env));
+        client.register_participant(&80u32, &a, &None, &None);
+
+        // `a` ya estaba y `b` se repite: ambos se omiten
+        let batch = soroban_sdk::vec![&env, b.clone(), a.clone(), c.clone(), b.clone()];
+        assert_eq!(client.register_participants(&80u32, &batch, &false), 2);
+        // Sólo firma el streamer
+        assert_eq!(env.auths().len(), 1);
+        assert_eq!(env.auths()[0].0, streamer);
+        assert_eq!(client.get_participants(&80u32), soroban_sdk::vec![&env, a, b, c]);
+
+        // Con firma de cada participante
+        let (d, e) = (Address::generate(&env), Address::generate(&env));
+        let batch = soroban_sdk::vec![&env, d.clone(), e.clone()];
+        assert_eq!(client.register_participants(&80u32, &batch, &true), 2);
+        let signers: std::vec::Vec<Address> = env.auths().iter().map(|(addr, _)| addr.clone()).collect();
+        assert_eq!(signers, [streamer, d, e]);
+        assert_eq!(client.participant_count(&80u32), 5);
+
+        assert_eq!(
+            client.try_register_participants(&80u32, &Vec::new(&env), &false),
+            Err(Ok(RouletteError::InvalidBatchSize))
+        );
+    }
+
+    #[test]
+    fn test_registro_por_lotes_multi_party_falla() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+        let commitment = make_commitment(&env, 8, &[0x08; 32]);
+        client.commit_draw(&streamer, &81u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &SessionConfig { multi_party: true, ..default_config(&token) });
+        assert_eq!(
+            client.try_register_participants(&81u32, &soroban_sdk::vec![&env, Address::generate(&env)], &false),
+            Err(Ok(RouletteError::NonceCommitmentRequired))
+        );
+    }
+
+    #[test]
+    fn test_allowlist_merkle() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+        let commitment = make_commitment(&env, 4, &[0x04; 32]);
+
+        let mut allowed: Vec<Address> = Vec::new(&env);
+        for _ in 0..3 {
+            allowed.push_back(Address::generate(&env));
         }
+        let root = merkle::root(&env, &allowed).unwrap();
+        client.commit_draw(&streamer, &90u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &SessionConfig { allowlist_root: Some(root.clone()), ..default_config(&token) });
 
-        // ── 2. Cálculo del Ganador ─────────────────────────────────────────
-        //
-        //  winner_index = secret_number mod num_participants
-        //  Misma fórmula que el circuito Noir → resultados idénticos y
-        //  verificables por cualquiera con el secreto revelado.
+        let member = allowed.get(1).unwrap();
+        let proof = merkle::proof(&env, &allowed, 1).unwrap();
+        client.register_participant(&90u32, &member, &None, &Some(proof.clone()));
+        assert_eq!(client.participant_count(&90u32), 1);
+
+        let outsider = Address::generate(&env);
+        assert_eq!(
+            client.try_register_participant(&90u32, &outsider, &None, &Some(proof)),
+            Err(Ok(RouletteError::NotOnAllowlist))
+        );
+        assert_eq!(
+            client.try_register_participant(&90u32, &allowed.get(0).unwrap(), &None, &None),
+            Err(Ok(RouletteError::AllowlistProofRequired))
+        );
+        // El streamer tampoco puede saltarse la allowlist con un lote
+        assert_eq!(
+            client.try_register_participants(&90u32, &soroban_sdk::vec![&env, outsider], &false),
+            Err(Ok(RouletteError::AllowlistProofRequired))
+        );
+    }
 
-        let num = session.num_participants as u64;
-        let winner_index = (secret_number % num) as u32;
+    /// Helper: sesión con entrada de 10 y 3 participantes que la pagan
+    fn paid_session(
+        env: &Env,
+        client: &ZkRouletteContractClient,
+        session_id: u32,
+    ) -> (Address, Address, soroban_sdk::Vec<Address>, [u8; 32]) {
+        let streamer = Address::generate(env);
+        let token = create_token(env, &streamer, 0);
+        let salt: [u8; 32] = [0x55; 32];
+        let commitment = make_commitment(env, 6, &salt);
+        let fee = EntryFee { token: token.clone(), amount: 10, streamer_cut_bps: 1_000, protocol_fee_bps: 500 };
+        client.commit_draw(&streamer, &session_id, &commitment, &CommitmentScheme::Sha256, &token, &0, &SessionConfig { entry_fee: fee.clone(), ..default_config(&token) });
 
-        let participants: Vec<Address> = env
-            .storage()
-            .temporary()
-            .get(&DataKey::Participants(session_id))
-            .unwrap_or_else(|| panic!("participants not found"));
+        let mut players = Vec::new(env);
+        for _ in 0..3 {
+            let p = Address::generate(env);
+            StellarAssetClient::new(env, &token).mint(&p, &10);
+            client.register_participant(&session_id, &p, &None, &None);
+            players.push_back(p);
+        }
+        client.close_registrations(&session_id);
+        (streamer, token, players, salt)
+    }
 
-        let winner_address = participants
-            .get(winner_index)
-            .unwrap_or_else(|| panic!("winner index out of bounds"));
+    #[test]
+    fn test_sorteo_con_entradas_paga_el_bote() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let treasury = Address::generate(&env);
+        client.set_fee_recipient(&treasury);
+        let (streamer, token, players, salt) = paid_session(&env, &client, 95);
+        let token_client = TokenClient::new(&env, &token);
+        assert_eq!(client.get_session(&95u32).entry_pool, 30);
+        assert_eq!(token_client.balance(&contract_id), 30);
 
-        // ── 3. Integración con Game Hub ────────────────────────────────────
-        //
-        //  game_id   = este contrato (el juego que llama al hub)
-        //  player1   = streamer   (organizador)
-        //  player2   = ganador    (determinado por el ZK commit-reveal)
-        //
-        //  player1_points = num_participants (datos del sorteo)
-        //  player2_points = winner_index     (para auditoría)
-        //
-        //  end_game(player1_won=false) porque el ganador es player2.
+        client.reveal_winner(&95u32, &6u64, &BytesN::from_array(&env, &salt));
 
-        let game_hub_addr: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::GameHub)
-            .unwrap_or_else(|| panic!("contract not initialized"));
+        // 30 → protocolo 5% = 1, streamer 10% = 3, ganador 26
+        let winner = players.get(client.get_session(&95u32).winner_indices.get(0).unwrap()).unwrap();
+        assert_eq!(token_client.balance(&treasury), 1);
+        assert_eq!(token_client.balance(&streamer), 3);
+        assert_eq!(token_client.balance(&winner), 26);
+        assert_eq!(token_client.balance(&contract_id), 0);
+    }
 
-        let game_hub = GameHubClient::new(&env, &game_hub_addr);
+    #[test]
+    fn test_entradas_reembolsadas_al_expirar() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let (_, token, players, _) = paid_session(&env, &client, 96);
+        let token_client = TokenClient::new(&env, &token);
 
-        game_hub.start_game(
-            &env.current_contract_address(),
-            &session_id,
-            &session.streamer,
-            &winner_address,
-            &(session.num_participants as i128),
-            &(winner_index as i128),
+        let p0 = players.get(0).unwrap();
+        assert_eq!(
+            client.try_claim_ticket_refund(&96u32, &p0),
+            Err(Ok(RouletteError::SessionNotExpired))
+        );
+        let deadline = client.get_session(&96u32).reveal_deadline;
+        env.ledger().with_mut(|l| l.sequence_number = deadline + 1);
+        client.expire_draw(&96u32);
+
+        for p in players.iter() {
+            client.claim_ticket_refund(&96u32, &p);
+            assert_eq!(token_client.balance(&p), 10);
+        }
+        assert_eq!(token_client.balance(&contract_id), 0);
+        assert_eq!(
+            client.try_claim_ticket_refund(&96u32, &p0),
+            Err(Ok(RouletteError::TicketAlreadyRefunded))
         );
+    }
+
+    #[test]
+    fn test_entrada_invalida_falla() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+        let commitment = make_commitment(&env, 6, &[0x55; 32]);
+        let fee = |amount, cut, protocol| EntryFee { token: token.clone(), amount, streamer_cut_bps: cut, protocol_fee_bps: protocol };
+        for bad in [fee(-1, 0, 0), fee(10, 0, 1_001), fee(10, 9_500, 1_000), fee(10, u32::MAX, 0)] {
+            assert_eq!(
+                client.try_commit_draw(&streamer, &97u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &SessionConfig { entry_fee: bad.clone(), ..default_config(&token) }),
+                Err(Ok(RouletteError::InvalidEntryFee))
+            );
+        }
+    }
+
+    #[test]
+    fn test_sorteo_ponderado_coincide_con_recorrido_lineal() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+        let secret: u64 = 0x00c0_ffee;
+        let salt: [u8; 32] = [0x66; 32];
+        let commitment = make_commitment(&env, secret, &salt);
+        client.commit_draw(&streamer, &96u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &SessionConfig { num_winners: 3, ..default_config(&token) });
 
-        // player2 (el ganador) es quien "gana" desde la perspectiva del Hub
-        game_hub.end_game(&session_id, &false);
+        let mut weights = [1u32; 40];
+        for i in 0..40u32 {
+            let p = Address::generate(&env);
+            client.register_participant(&96u32, &p, &None, &None);
+            if i % 3 == 0 {
+                weights[i as usize] = (i * 7) % 13 + 2;
+                client.set_participant_weight(&96u32, &p, &weights[i as usize]);
+            }
+            assert_eq!(client.get_participant_weight(&96u32, &p), Some(weights[i as usize]));
+        }
+        let total: u32 = weights.iter().sum();
+        assert_eq!(client.get_session(&96u32).total_weight, total);
 
-        // ── 4. Persistir resultado y emitir evento ─────────────────────────
-        session.phase = DrawPhase::Revealed;
-        session.winner_index = winner_index;
-        env.storage().temporary().set(&DataKey::Session(session_id), &session);
+        client.close_registrations(&96u32);
+        let salt_bn = BytesN::from_array(&env, &salt);
+        client.reveal_winner(&96u32, &secret, &salt_bn);
 
-        // Evento con toda la información del sorteo concluido
-        // topic: ("winner_revealed", session_id)
-        // data:  (winner_index, winner_address_str, secret_number, num_participants)
-        env.events().publish(
-            (Symbol::new(&env, "winner"), session_id),
-            (winner_index, winner_address, secret_number, session.num_participants),
+        // Misma extracción recorriendo los pesos linealmente
+        let record = client.get_draw_record(&96u32);
+        let expected = selection::derive_weighted_winners(
+            &env,
+            secret,
+            &salt_bn,
+            &record.draw_entropy,
+            total as u64,
+            3,
+            |ticket, taken| {
+                let mut below: u64 = 0;
+                for (i, w) in weights.iter().enumerate() {
+                    if taken.iter().any(|(t, _)| t == i as u32) {
+                        continue;
+                    }
+                    below += *w as u64;
+                    if ticket < below {
+                        return (i as u32, *w);
+                    }
+                }
+                unreachable!()
+            },
         );
+        assert_eq!(record.winner_indices, expected);
+        assert_eq!(record.total_weight, total);
     }
 
-    // ════════════════════════════════════════════════════════════════════════
-    //  CONSULTAS (read-only)
-    // ════════════════════════════════════════════════════════════════════════
+    #[test]
+    fn test_peso_de_participante() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+        let commitment = make_commitment(&env, 2, &[0x12; 32]);
+        client.commit_draw(&streamer, &97u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &default_config(&token));
+        let p1 = Address::generate(&env);
+        let p2 = Address::generate(&env);
+        client.register_participant(&97u32, &p1, &None, &None);
+        client.register_participant(&97u32, &p2, &None, &None);
 
-    /// Retorna el estado de la sesión (para el frontend)
-    pub fn get_session(env: Env, session_id: u32) -> DrawSession {
-        env.storage()
-            .temporary()
-            .get(&DataKey::Session(session_id))
-            .unwrap_or_else(|| panic!("session not found"))
+        assert_eq!(
+            client.try_set_participant_weight(&97u32, &p1, &0),
+            Err(Ok(RouletteError::InvalidWeight))
+        );
+        assert_eq!(
+            client.try_set_participant_weight(&97u32, &p1, &(weights::MAX_WEIGHT + 1)),
+            Err(Ok(RouletteError::InvalidWeight))
+        );
+        assert_eq!(
+            client.try_set_participant_weight(&97u32, &Address::generate(&env), &2),
+            Err(Ok(RouletteError::NotRegistered))
+        );
+
+        // Subir y volver a 1 deja la sesión sin pesos
+        client.set_participant_weight(&97u32, &p2, &weights::MAX_WEIGHT);
+        assert_eq!(client.get_session(&97u32).total_weight, weights::MAX_WEIGHT + 1);
+        client.set_participant_weight(&97u32, &p2, &1);
+        assert_eq!(client.get_session(&97u32).total_weight, 2);
+        assert_eq!(client.get_participant_weight(&97u32, &p2), Some(1));
+
+        // Con casi todos los boletos, p2 gana
+        client.set_participant_weight(&97u32, &p2, &weights::MAX_WEIGHT);
+        client.close_registrations(&97u32);
+        assert_eq!(
+            client.try_set_participant_weight(&97u32, &p2, &2),
+            Err(Ok(RouletteError::RegistrationClosed))
+        );
+        client.reveal_winner(&97u32, &2u64, &BytesN::from_array(&env, &[0x12; 32]));
+        assert_eq!(client.get_session(&97u32).winner_indices, soroban_sdk::vec![&env, 1u32]);
+
+        // Multi-party: el pool lo forman los nonces revelados
+        let (players, _, _) = multi_party_session(&env, &client, 98);
+        assert_eq!(
+            client.try_set_participant_weight(&98u32, &players.get(0).unwrap(), &2),
+            Err(Ok(RouletteError::WeightsNotSupported))
+        );
     }
 
-    /// Retorna la lista de participantes de una sesión
-    pub fn get_participants(env: Env, session_id: u32) -> Vec<Address> {
-        env.storage()
-            .temporary()
-            .get(&DataKey::Participants(session_id))
-            .unwrap_or_else(|| panic!("session not found"))
+    #[test]
+    fn test_saldo_minimo_para_inscribirse() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+        let holder = Address::generate(&env);
+        let gate_token = create_token(&env, &holder, 50);
+        let commitment = make_commitment(&env, 3, &[0x21; 32]);
+
+        let bad = BalanceGate { token: gate_token.clone(), min_balance: -1, recheck_at_reveal: false };
+        assert_eq!(
+            client.try_commit_draw(&streamer, &99u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &SessionConfig { balance_gate: bad.clone(), ..default_config(&token) }),
+            Err(Ok(RouletteError::InvalidBalanceGate))
+        );
+
+        let gate = BalanceGate { token: gate_token.clone(), min_balance: 50, recheck_at_reveal: false };
+        client.commit_draw(&streamer, &99u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &SessionConfig { balance_gate: gate.clone(), ..default_config(&token) });
+        client.register_participant(&99u32, &holder, &None, &None);
+
+        let outsider = Address::generate(&env);
+        StellarAssetClient::new(&env, &gate_token).mint(&outsider, &49);
+        assert_eq!(
+            client.try_register_participant(&99u32, &outsider, &None, &None),
+            Err(Ok(RouletteError::InsufficientTokenBalance))
+        );
+        // Un lote con una sola cuenta sin saldo se rechaza entero
+        let ok = Address::generate(&env);
+        StellarAssetClient::new(&env, &gate_token).mint(&ok, &50);
+        assert_eq!(
+            client.try_register_participants(&99u32, &soroban_sdk::vec![&env, ok.clone(), outsider], &false),
+            Err(Ok(RouletteError::InsufficientTokenBalance))
+        );
+        assert_eq!(client.register_participants(&99u32, &soroban_sdk::vec![&env, ok], &false), 1);
+        assert_eq!(client.participant_count(&99u32), 2);
     }
 
-    /// Retorna el número de participantes registrados
-    pub fn participant_count(env: Env, session_id: u32) -> u32 {
-        let session: DrawSession = env
-            .storage()
-            .temporary()
-            .get(&DataKey::Session(session_id))
-            .unwrap_or_else(|| panic!("session not found"));
-        session.num_participants
+    #[test]
+    fn test_ganador_sin_saldo_sustituido_en_reveal() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+        let gate_token = create_token(&env, &streamer, 0);
+        let salt: [u8; 32] = [0x23; 32];
+        let commitment = make_commitment(&env, 5, &salt);
+        let gate = BalanceGate { token: gate_token.clone(), min_balance: 10, recheck_at_reveal: true };
+        client.commit_draw(&streamer, &100u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &SessionConfig { balance_gate: gate.clone(), ..default_config(&token) });
+
+        let mut players = Vec::new(&env);
+        for _ in 0..4 {
+            let p = Address::generate(&env);
+            StellarAssetClient::new(&env, &gate_token).mint(&p, &10);
+            client.register_participant(&100u32, &p, &None, &None);
+            players.push_back(p);
+        }
+        client.close_registrations(&100u32);
+
+        // El ganador extraído vende sus tokens antes del reveal
+        let salt_bn = BytesN::from_array(&env, &salt);
+        let entropy = client.get_session(&100u32).close_entropy;
+        let drawn = selection::derive_winners(&env, 5, &salt_bn, &entropy, 4, 1).get(0).unwrap();
+        TokenClient::new(&env, &gate_token).transfer(&players.get(drawn).unwrap(), &streamer, &10);
+
+        client.reveal_winner(&100u32, &5u64, &salt_bn);
+        let winner = (drawn + 1) % 4;
+        assert_eq!(client.get_session(&100u32).winner_indices, soroban_sdk::vec![&env, winner]);
+        assert_eq!(client.get_draw_record(&100u32).winner_addresses.get(0).unwrap(), players.get(winner).unwrap());
     }
-}
 
-// ═══════════════════════════════════════════════════════════════════════════
-//  TESTS
-//  Ejecutar con: cargo test
-// ═══════════════════════════════════════════════════════════════════════════
-#[cfg(test)]
-mod test {
-    use super::*;
-    use soroban_sdk::{
-        testutils::{Address as _, Events},
-        Address, Env,
-    };
+    #[test]
+    fn test_arbol_de_pesos_acotado_por_la_capacidad() {
+        use soroban_sdk::testutils::storage::Temporary as _;
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+        let commitment = make_commitment(&env, 2, &[0x13; 32]);
+        let config = SessionConfig { max_participants: 5, ..default_config(&token) };
+        client.commit_draw(&streamer, &112u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &config);
+        let mut players = Vec::new(&env);
+        for _ in 0..5 {
+            let p = Address::generate(&env);
+            client.register_participant(&112u32, &p, &None, &None);
+            players.push_back(p);
+        }
+        let tree = client.get_session(&112u32).weight_tree;
+        let node = |n: u32| -> Option<u32> {
+            env.as_contract(&contract_id, || env.storage().temporary().get(&DataKey::WeightNode(tree, n)))
+        };
 
-    /// Construye el commitment SHA-256(secret_be8 || salt32) en Rust para tests
-    fn make_commitment(env: &Env, secret: u64, salt: &[u8; 32]) -> BytesN<32> {
-        let mut preimage = Bytes::new(env);
-        for b in secret.to_be_bytes().iter() {
-            preimage.push_back(*b);
+        // Camino 1 → 2 → 4; el 8 ya supera la capacidad
+        client.set_participant_weight(&112u32, &players.get(0).unwrap(), &3);
+        assert_eq!((node(1), node(2), node(4), node(8)), (Some(2), Some(2), Some(2), None));
+
+        // El árbol sobrevive a la sesión aunque ésta se extienda después
+        env.ledger().with_mut(|l| l.sequence_number += 1_000);
+        client.unregister_participant(&112u32, &players.get(4).unwrap());
+        env.as_contract(&contract_id, || {
+            let storage = env.storage().temporary();
+            assert!(storage.get_ttl(&DataKey::WeightNode(tree, 4)) >= storage.get_ttl(&DataKey::Session(112)));
+        });
+
+        // La baja de un peso 3 deja el árbol a 0
+        client.unregister_participant(&112u32, &players.get(0).unwrap());
+        assert_eq!((node(1), node(2), node(4)), (None, None, None));
+        assert_eq!(client.get_session(&112u32).total_weight, 3);
+        assert_eq!(client.get_participant_weight(&112u32, &players.get(3).unwrap()), Some(1));
+    }
+
+    #[test]
+    fn test_id_reutilizado_no_hereda_pesos() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+        let commitment = make_commitment(&env, 2, &[0x14; 32]);
+        let config = SessionConfig { max_participants: 5, ..default_config(&token) };
+        client.commit_draw(&streamer, &113u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &config);
+        let old = Address::generate(&env);
+        client.register_participant(&113u32, &old, &None, &None);
+        client.set_participant_weight(&113u32, &old, &weights::MAX_WEIGHT);
+        client.cancel_draw(&113u32);
+
+        // La sesión caduca; su árbol de pesos sigue en storage
+        env.ledger().with_mut(|l| l.sequence_number += TTL_LEDGERS + 1);
+        client.commit_draw(&streamer, &113u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &config);
+        let new = Address::generate(&env);
+        client.register_participant(&113u32, &new, &None, &None);
+        assert_eq!(client.get_participant_weight(&113u32, &new), Some(1));
+        client.set_participant_weight(&113u32, &new, &2);
+        assert_eq!(client.get_session(&113u32).total_weight, 2);
+    }
+
+    #[test]
+    fn test_baja_de_participante() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+        let token_client = TokenClient::new(&env, &token);
+        let salt: [u8; 32] = [0x31; 32];
+        let commitment = make_commitment(&env, 8, &salt);
+        let fee = EntryFee { token: token.clone(), amount: 10, streamer_cut_bps: 0, protocol_fee_bps: 0 };
+        client.commit_draw(&streamer, &101u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &SessionConfig { entry_fee: fee.clone(), ..default_config(&token) });
+
+        let mut players = Vec::new(&env);
+        for _ in 0..3 {
+            let p = Address::generate(&env);
+            StellarAssetClient::new(&env, &token).mint(&p, &10);
+            client.register_participant(&101u32, &p, &None, &None);
+            players.push_back(p);
         }
-        for b in salt.iter() {
-            preimage.push_back(*b);
+        let (p0, p1, p2) = (players.get(0).unwrap(), players.get(1).unwrap(), players.get(2).unwrap());
+        client.set_participant_weight(&101u32, &p2, &5);
+        let hash_before = client.get_session(&101u32).participants_hash;
+
+        // p0 sale: recupera su entrada y p2 (el último) ocupa el índice 0
+        client.unregister_participant(&101u32, &p0);
+        assert_eq!(token_client.balance(&p0), 10);
+        let session = client.get_session(&101u32);
+        assert_eq!(session.num_participants, 2);
+        assert_eq!(session.entry_pool, 20);
+        assert_eq!(session.total_weight, 6);
+        assert_ne!(session.participants_hash, hash_before);
+        assert_eq!(client.get_participants(&101u32), soroban_sdk::vec![&env, p2.clone(), p1.clone()]);
+        assert_eq!(client.get_participant_index(&101u32, &p2), Some(0));
+        assert_eq!(client.get_participant_weight(&101u32, &p2), Some(5));
+        assert_eq!(client.get_participant_index(&101u32, &p0), None);
+        assert_eq!(
+            client.try_unregister_participant(&101u32, &p0),
+            Err(Ok(RouletteError::NotRegistered))
+        );
+
+        // Puede volver a inscribirse (y paga de nuevo) al final de la lista
+        client.register_participant(&101u32, &p0, &None, &None);
+        assert_eq!(client.get_participant_index(&101u32, &p0), Some(2));
+        assert_eq!(token_client.balance(&p0), 0);
+
+        client.close_registrations(&101u32);
+        assert_eq!(
+            client.try_unregister_participant(&101u32, &p1),
+            Err(Ok(RouletteError::RegistrationClosed))
+        );
+        client.reveal_winner(&101u32, &8u64, &BytesN::from_array(&env, &salt));
+        // Bote de 3 entradas para el ganador
+        let winner = client.get_draw_record(&101u32).winner_addresses.get(0).unwrap();
+        assert_eq!(token_client.balance(&winner), 30);
+        assert_eq!(token_client.balance(&contract_id), 0);
+    }
+
+    #[test]
+    fn test_cancelar_tras_el_cierre() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let (streamer, token, salt) = closed_session(&env, &client, 102, 100, 50, None);
+        let token_client = TokenClient::new(&env, &token);
+
+        client.cancel_draw(&102u32);
+
+        // El premio vuelve al streamer; la fianza se confisca como al expirar
+        assert_eq!(token_client.balance(&streamer), 100);
+        assert_eq!(token_client.balance(&contract_id), 50);
+        assert!(client.get_outstanding_bonds(&streamer).is_empty());
+        assert!(client.get_session(&102u32).phase == DrawPhase::Cancelled);
+        assert_eq!(client.get_late_cancellations(&streamer), 1);
+
+        // Sin `slash_recipient`, los participantes se reparten la fianza
+        let participants = client.get_participants(&102u32);
+        for p in participants.iter() {
+            client.claim_bond_share(&102u32, &p);
+            assert_eq!(token_client.balance(&p), 25);
         }
-        env.crypto().sha256(&preimage)
+        assert_eq!(token_client.balance(&contract_id), 0);
+
+        assert_eq!(client.try_cancel_draw(&102u32), Err(Ok(RouletteError::SessionNotCancellable)));
+        assert_eq!(
+            client.try_reveal_winner(&102u32, &9u64, &BytesN::from_array(&env, &salt)),
+            Err(Ok(RouletteError::SessionNotClosed))
+        );
     }
 
-    /// Helper: despliega el contrato y lo inicializa con un mock de Game Hub
-    fn setup() -> (Env, Address, Address) {
-        let env = Env::default();
-        env.mock_all_auths();
+    #[test]
+    fn test_cancelar_tras_el_plazo_de_reveal_falla() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let (streamer, token, _) = closed_session(&env, &client, 108, 100, 50, None);
+        let token_client = TokenClient::new(&env, &token);
+
+        let deadline = client.get_session(&108u32).reveal_deadline;
+        env.ledger().with_mut(|l| l.sequence_number = deadline + 1);
+        assert_eq!(client.try_cancel_draw(&108u32), Err(Ok(RouletteError::SessionNotCancellable)));
+        assert_eq!(client.get_late_cancellations(&streamer), 0);
 
-        let contract_id = env.register_contract(None, ZkRouletteContract);
-        // En tests se usa un mock de Game Hub (dirección aleatoria)
-        let game_hub = Address::generate(&env);
+        // La fianza se confisca igual
+        client.expire_draw(&108u32);
+        assert_eq!(token_client.balance(&streamer), 100);
+        assert!(client.get_session(&108u32).phase == DrawPhase::Expired);
+        let participants = client.get_participants(&108u32);
+        client.claim_bond_share(&108u32, &participants.get(0).unwrap());
+        assert_eq!(token_client.balance(&participants.get(0).unwrap()), 25);
+    }
 
+    #[test]
+    fn test_cancelar_sorteo_abierto_reembolsa_entradas() {
+        let (env, contract_id, _) = setup();
         let client = ZkRouletteContractClient::new(&env, &contract_id);
-        client.initialize(&game_hub);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+        let token_client = TokenClient::new(&env, &token);
+        let commitment = make_commitment(&env, 4, &[0x41; 32]);
+        let fee = EntryFee { token: token.clone(), amount: 10, streamer_cut_bps: 0, protocol_fee_bps: 0 };
+        client.commit_draw(&streamer, &103u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &SessionConfig { entry_fee: fee.clone(), ..default_config(&token) });
+        let p = Address::generate(&env);
+        StellarAssetClient::new(&env, &token).mint(&p, &10);
+        client.register_participant(&103u32, &p, &None, &None);
 
-        (env, contract_id, game_hub)
+        assert_eq!(
+            client.try_claim_ticket_refund(&103u32, &p),
+            Err(Ok(RouletteError::SessionNotExpired))
+        );
+        client.cancel_draw(&103u32);
+        assert_eq!(client.get_late_cancellations(&streamer), 0);
+
+        client.claim_ticket_refund(&103u32, &p);
+        assert_eq!(token_client.balance(&p), 10);
+        assert_eq!(
+            client.try_claim_ticket_refund(&103u32, &p),
+            Err(Ok(RouletteError::TicketAlreadyRefunded))
+        );
+        assert_eq!(
+            client.try_register_participant(&103u32, &Address::generate(&env), &None, &None),
+            Err(Ok(RouletteError::RegistrationClosed))
+        );
     }
 
     #[test]
-    fn test_flujo_completo() {
+    fn test_cierre_programado_por_plazo() {
         let (env, contract_id, _) = setup();
         let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+        let commitment = make_commitment(&env, 7, &[0x51; 32]);
+        env.ledger().with_mut(|l| l.sequence_number = 100);
+        let deadline = env.ledger().sequence() + 10;
+
+        let past = SessionConfig { registration_deadline: env.ledger().sequence(), ..default_config(&token) };
+        assert_eq!(
+            client.try_commit_draw(&streamer, &104u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &past),
+            Err(Ok(RouletteError::InvalidRegistrationDeadline))
+        );
+        let config = SessionConfig {
+            registration_deadline: deadline,
+            allow_early_close: false,
+            reveal_window_ledgers: 500,
+            ..default_config(&token)
+        };
+        client.commit_draw(&streamer, &104u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &config);
+        client.register_participant(&104u32, &Address::generate(&env), &None, &None);
+        client.register_participant(&104u32, &Address::generate(&env), &None, &None);
+
+        // Antes del plazo nadie puede cerrar, ni siquiera el streamer
+        assert_eq!(client.try_close_registrations(&104u32), Err(Ok(RouletteError::EarlyCloseNotAllowed)));
+        assert_eq!(client.try_finalize_close(&104u32), Err(Ok(RouletteError::CloseNotDue)));
+
+        env.ledger().with_mut(|l| l.sequence_number = deadline + 1);
+        assert_eq!(
+            client.try_register_participant(&104u32, &Address::generate(&env), &None, &None),
+            Err(Ok(RouletteError::RegistrationClosed))
+        );
+        client.finalize_close(&104u32);
+        let session = client.get_session(&104u32);
+        assert!(session.phase == DrawPhase::Closed);
+        assert_eq!(session.reveal_deadline, deadline + 1 + 500);
+        client.reveal_winner(&104u32, &7u64, &BytesN::from_array(&env, &[0x51; 32]));
+    }
 
+    #[test]
+    fn test_plazo_vencido_sin_minimo_cancela_y_reembolsa() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
         let streamer = Address::generate(&env);
-        let p1 = Address::generate(&env);
-        let p2 = Address::generate(&env);
-        let p3 = Address::generate(&env);
+        let token = create_token(&env, &streamer, 100);
+        let token_client = TokenClient::new(&env, &token);
+        let commitment = make_commitment(&env, 7, &[0x53; 32]);
+        env.ledger().with_mut(|l| l.sequence_number = 100);
+        let deadline = env.ledger().sequence() + 10;
+        let config = SessionConfig {
+            registration_deadline: deadline,
+            allow_early_close: false,
+            min_participants: 3,
+            entry_fee: EntryFee { token: token.clone(), amount: 10, streamer_cut_bps: 0, protocol_fee_bps: 0 },
+            ..default_config(&token)
+        };
+        client.commit_draw(&streamer, &109u32, &commitment, &CommitmentScheme::Sha256, &token, &100, &config);
+        let p = Address::generate(&env);
+        StellarAssetClient::new(&env, &token).mint(&p, &10);
+        client.register_participant(&109u32, &p, &None, &None);
+        let q = Address::generate(&env);
+        StellarAssetClient::new(&env, &token).mint(&q, &10);
+        client.register_participant(&109u32, &q, &None, &None);
 
-        let secret: u64 = 13;
-        let salt: [u8; 32] = [0x1a; 32];
-        let commitment = make_commitment(&env, secret, &salt);
-        let salt_bn: BytesN<32> = BytesN::from_array(&env, &salt);
+        env.ledger().with_mut(|l| l.sequence_number = deadline + 1);
+        assert_eq!(client.try_close_registrations(&109u32), Err(Ok(RouletteError::NotEnoughParticipants)));
+        // Cualquiera desbloquea la sesión, sin contar contra el streamer
+        client.finalize_close(&109u32);
+        assert!(client.get_session(&109u32).phase == DrawPhase::Cancelled);
+        assert_eq!(token_client.balance(&streamer), 100);
+        assert_eq!(client.get_late_cancellations(&streamer), 0);
 
-        // Fase 1: Commit
-        client.commit_draw(&streamer, &42u32, &commitment);
+        client.claim_ticket_refund(&109u32, &p);
+        assert_eq!(token_client.balance(&p), 10);
+    }
 
-        // Fase 2: Registro
-        client.register_participant(&42u32, &p1);
-        client.register_participant(&42u32, &p2);
-        client.register_participant(&42u32, &p3);
+    #[test]
+    fn test_cierre_programado_por_tope() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+        let commitment = make_commitment(&env, 7, &[0x52; 32]);
 
-        assert_eq!(client.participant_count(&42u32), 3);
+        let config = SessionConfig { max_participants: 3, ..default_config(&token) };
+        client.commit_draw(&streamer, &105u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &config);
+        for _ in 0..2 {
+            client.register_participant(&105u32, &Address::generate(&env), &None, &None);
+        }
+        assert_eq!(client.try_finalize_close(&105u32), Err(Ok(RouletteError::CloseNotDue)));
+        client.register_participant(&105u32, &Address::generate(&env), &None, &None);
+        assert_eq!(
+            client.try_register_participant(&105u32, &Address::generate(&env), &None, &None),
+            Err(Ok(RouletteError::MaxParticipantsReached))
+        );
+        client.finalize_close(&105u32);
+        assert!(client.get_session(&105u32).phase == DrawPhase::Closed);
 
-        // Cerrar inscripciones
-        client.close_registrations(&42u32);
+        // Con `allow_early_close` el streamer puede cerrar antes del tope
+        let early = make_commitment(&env, 7, &[0x53; 32]);
+        client.commit_draw(&streamer, &106u32, &early, &CommitmentScheme::Sha256, &token, &0, &config);
+        client.register_participant(&106u32, &Address::generate(&env), &None, &None);
+        client.register_participant(&106u32, &Address::generate(&env), &None, &None);
+        client.close_registrations(&106u32);
+    }
+
+    #[test]
+    fn test_configuracion_de_sesion() {
+        let (env, contract_id, _) = setup();
+        let client = ZkRouletteContractClient::new(&env, &contract_id);
+        let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
+        let commitment = make_commitment(&env, 3, &[0x61; 32]);
+
+        // Límites del contrato
+        let invalid = [
+            SessionConfig { min_participants: 1, ..default_config(&token) },
+            SessionConfig { num_winners: 3, max_participants: 2, ..default_config(&token) },
+            SessionConfig { min_participants: 5, max_participants: 4, ..default_config(&token) },
+            SessionConfig { max_participants: MAX_PARTICIPANTS + 1, ..default_config(&token) },
+            SessionConfig { reveal_window_ledgers: MIN_WINDOW_LEDGERS - 1, ..default_config(&token) },
+            SessionConfig { nonce_window_ledgers: MAX_WINDOW_LEDGERS + 1, ..default_config(&token) },
+            SessionConfig { registration_deadline: 0, allow_early_close: false, ..default_config(&token) },
+        ];
+        for config in invalid.iter() {
+            assert_eq!(
+                client.try_commit_draw(&streamer, &107u32, &commitment, &CommitmentScheme::Sha256, &token, &0, config),
+                Err(Ok(RouletteError::InvalidSessionConfig))
+            );
+        }
+        let far = SessionConfig { registration_deadline: MAX_REGISTRATION_LEDGERS + 1, ..default_config(&token) };
+        assert_eq!(
+            client.try_commit_draw(&streamer, &107u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &far),
+            Err(Ok(RouletteError::InvalidRegistrationDeadline))
+        );
 
-        // Fase 3: Reveal (sin Game Hub real en tests — mock_all_auths ignora la llamada externa)
-        // winner_index = 13 % 3 = 1 → p2
-        // Nota: en test real con Game Hub mock este llamado fallará a menos que
-        // registres el contrato Game Hub. Para el hackathon, prueba en Testnet.
+        // Mínimo propio de participantes y sin informe al Game Hub
+        let config = SessionConfig { min_participants: 3, report_to_hub: false, ..default_config(&token) };
+        client.commit_draw(&streamer, &107u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &config);
+        assert_eq!(client.get_session_config(&107u32).min_participants, 3);
+        assert!(!client.get_session_config(&107u32).report_to_hub);
+        client.register_participant(&107u32, &Address::generate(&env), &None, &None);
+        client.register_participant(&107u32, &Address::generate(&env), &None, &None);
+        assert_eq!(client.try_close_registrations(&107u32), Err(Ok(RouletteError::NotEnoughParticipants)));
+        client.register_participant(&107u32, &Address::generate(&env), &None, &None);
+        client.close_registrations(&107u32);
+        client.reveal_winner(&107u32, &3u64, &BytesN::from_array(&env, &[0x61; 32]));
+        assert_eq!(client.get_draw_record(&107u32).winner_indices.len(), 1);
+        assert_eq!(client.get_session(&107u32).hub_status, HubStatus::Disabled);
+        assert_eq!(client.try_retry_hub_report(&107u32), Err(Ok(RouletteError::HubReportNotPending)));
     }
 
     #[test]
-    #[should_panic(expected = "session already exists")]
     fn test_no_doble_commit() {
         let (env, contract_id, _) = setup();
         let client = ZkRouletteContractClient::new(&env, &contract_id);
         let streamer = Address::generate(&env);
+        let token = create_token(&env, &streamer, 100);
         let salt: [u8; 32] = [0x00; 32];
         let commitment = make_commitment(&env, 1, &salt);
 
-        client.commit_draw(&streamer, &1u32, &commitment);
-        client.commit_draw(&streamer, &1u32, &commitment); // debe fallar
+        client.commit_draw(&streamer, &1u32, &commitment, &CommitmentScheme::Sha256, &token, &10, &default_config(&token));
+        // debe fallar
+        assert_eq!(
+            client.try_commit_draw(&streamer, &1u32, &commitment, &CommitmentScheme::Sha256, &token, &10, &default_config(&token)),
+            Err(Ok(RouletteError::SessionAlreadyExists))
+        );
     }
 
     #[test]
-    #[should_panic(expected = "commitment mismatch")]
     fn test_reveal_commitment_invalido() {
         let (env, contract_id, _) = setup();
         let client = ZkRouletteContractClient::new(&env, &contract_id);
         let streamer = Address::generate(&env);
         let p1 = Address::generate(&env);
         let p2 = Address::generate(&env);
+        let token = create_token(&env, &streamer, 100);
 
         // Commit con secreto = 5
         let real_secret: u64 = 5;
         let salt_arr: [u8; 32] = [0xab; 32];
         let commitment = make_commitment(&env, real_secret, &salt_arr);
-        client.commit_draw(&streamer, &99u32, &commitment);
+        client.commit_draw(&streamer, &99u32, &commitment, &CommitmentScheme::Sha256, &token, &100, &default_config(&token));
 
-        client.register_participant(&99u32, &p1);
-        client.register_participant(&99u32, &p2);
+        client.register_participant(&99u32, &p1, &None, &None);
+        client.register_participant(&99u32, &p2, &None, &None);
         client.close_registrations(&99u32);
 
-        // Intenta revelar con un secreto DIFERENTE → debe paniquear
+        // Intenta revelar con un secreto DIFERENTE → debe fallar
         let wrong_salt: BytesN<32> = BytesN::from_array(&env, &[0xab; 32]);
-        client.reveal_winner(&99u32, &999u64, &wrong_salt); // secreto incorrecto
+        // secreto incorrecto
+        assert_eq!(
+            client.try_reveal_winner(&99u32, &999u64, &wrong_salt),
+            Err(Ok(RouletteError::CommitmentMismatch))
+        );
     }
 
     #[test]
-    #[should_panic(expected = "need at least 2 participants")]
     fn test_cierre_sin_participantes_suficientes() {
         let (env, contract_id, _) = setup();
         let client = ZkRouletteContractClient::new(&env, &contract_id);
         let streamer = Address::generate(&env);
         let p1 = Address::generate(&env);
+        let token = create_token(&env, &streamer, 0);
         let salt_arr: [u8; 32] = [0x00; 32];
         let commitment = make_commitment(&env, 1, &salt_arr);
 
-        client.commit_draw(&streamer, &7u32, &commitment);
-        client.register_participant(&7u32, &p1); // solo 1
-        client.close_registrations(&7u32);       // debe fallar
+        client.commit_draw(&streamer, &7u32, &commitment, &CommitmentScheme::Sha256, &token, &0, &default_config(&token));
+        client.register_participant(&7u32, &p1, &None, &None); // solo 1
+        // debe fallar
+        assert_eq!(
+            client.try_close_registrations(&7u32),
+            Err(Ok(RouletteError::NotEnoughParticipants))
+        );
     }
 }
@@ -0,0 +1,152 @@
+// ═══════════════════════════════════════════════════════════════════════════
+//  Allowlist Merkle
+//
+//  Árbol binario SHA-256 sobre las direcciones que pueden inscribirse:
+//
+//    hoja = SHA-256( 0x00 || xdr(address) )
+//    nodo = SHA-256( 0x01 || min(a, b) || max(a, b) )
+//
+//  Los hijos se ordenan por bytes, así la prueba es sólo la lista de
+//  hermanos, sin bits de posición. Un nodo sin pareja sube de nivel sin
+//  cambios. Los prefijos 0x00 / 0x01 impiden presentar un nodo interno
+//  como si fuera una hoja.
+//
+//  `root` y `proof` construyen el árbol fuera de la cadena (un script o
+//  test con `Env::default()`; ver `examples/allowlist.rs`); `verify` es la
+//  comprobación que hace `register_participant`.
+// ═══════════════════════════════════════════════════════════════════════════
+
+use soroban_sdk::{xdr::ToXdr, Address, Bytes, BytesN, Env, Vec};
+
+/// Profundidad máxima de una prueba (2^32 hojas)
+pub const MAX_PROOF_DEPTH: u32 = 32;
+
+/// Hoja del árbol para `address`
+pub fn leaf(env: &Env, address: &Address) -> BytesN<32> {
+    let mut preimage = Bytes::from_array(env, &[0x00]);
+    preimage.append(&address.clone().to_xdr(env));
+    env.crypto().sha256(&preimage).into()
+}
+
+/// Nodo padre de `a` y `b` (conmutativo)
+fn node(env: &Env, a: &BytesN<32>, b: &BytesN<32>) -> BytesN<32> {
+    let (lo, hi) = if a.to_array() <= b.to_array() { (a, b) } else { (b, a) };
+    let mut preimage = Bytes::from_array(env, &[0x01]);
+    preimage.extend_from_array(&lo.to_array());
+    preimage.extend_from_array(&hi.to_array());
+    env.crypto().sha256(&preimage).into()
+}
+
+/// Comprueba que `address` es una hoja del árbol con raíz `root`
+pub fn verify(env: &Env, root: &BytesN<32>, address: &Address, proof: &Vec<BytesN<32>>) -> bool {
+    if proof.len() > MAX_PROOF_DEPTH {
+        return false;
+    }
+    let mut current = leaf(env, address);
+    for sibling in proof.iter() {
+        current = node(env, &current, &sibling);
+    }
+    current == *root
+}
+
+/// Nivel superior del árbol
+fn next_level(env: &Env, level: &Vec<BytesN<32>>) -> Vec<BytesN<32>> {
+    let mut next: Vec<BytesN<32>> = Vec::new(env);
+    let mut i = 0;
+    while i < level.len() {
+        let left = level.get(i).unwrap();
+        match level.get(i + 1) {
+            Some(right) => next.push_back(node(env, &left, &right)),
+            None => next.push_back(left),
+        }
+        i += 2;
+    }
+    next
+}
+
+/// Hojas de `addresses`, en el mismo orden
+fn leaves(env: &Env, addresses: &Vec<Address>) -> Vec<BytesN<32>> {
+    let mut level: Vec<BytesN<32>> = Vec::new(env);
+    for address in addresses.iter() {
+        level.push_back(leaf(env, &address));
+    }
+    level
+}
+
+/// Raíz del árbol de `addresses` (None si la lista está vacía)
+pub fn root(env: &Env, addresses: &Vec<Address>) -> Option<BytesN<32>> {
+    let mut level = leaves(env, addresses);
+    if level.is_empty() {
+        return None;
+    }
+    while level.len() > 1 {
+        level = next_level(env, &level);
+    }
+    level.get(0)
+}
+
+/// Prueba de inclusión de `addresses[index]` (None si el índice no existe)
+pub fn proof(env: &Env, addresses: &Vec<Address>, index: u32) -> Option<Vec<BytesN<32>>> {
+    if index >= addresses.len() {
+        return None;
+    }
+    let mut level = leaves(env, addresses);
+    let mut position = index;
+    let mut siblings: Vec<BytesN<32>> = Vec::new(env);
+    while level.len() > 1 {
+        // Un nodo sin pareja sube sin hermano que añadir
+        if let Some(sibling) = level.get(position ^ 1) {
+            siblings.push_back(sibling);
+        }
+        level = next_level(env, &level);
+        position /= 2;
+    }
+    Some(siblings)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::testutils::Address as _;
+
+    #[test]
+    fn test_pruebas_de_todas_las_hojas() {
+        let env = Env::default();
+        let mut addresses: Vec<Address> = Vec::new(&env);
+        for _ in 0..5 {
+            addresses.push_back(Address::generate(&env));
+        }
+        let root = root(&env, &addresses).unwrap();
+
+        for i in 0..addresses.len() {
+            let p = proof(&env, &addresses, i).unwrap();
+            assert!(verify(&env, &root, &addresses.get(i).unwrap(), &p));
+            // La prueba no sirve para otra dirección
+            let other = addresses.get((i + 1) % addresses.len()).unwrap();
+            assert!(!verify(&env, &root, &other, &p));
+        }
+        assert!(!verify(&env, &root, &Address::generate(&env), &proof(&env, &addresses, 0).unwrap()));
+        assert_eq!(proof(&env, &addresses, 5), None);
+    }
+
+    #[test]
+    fn test_arbol_de_una_hoja_y_prueba_demasiado_larga() {
+        let env = Env::default();
+        let a = Address::generate(&env);
+        let single = soroban_sdk::vec![&env, a.clone()];
+        assert_eq!(root(&env, &single), Some(leaf(&env, &a)));
+        assert!(verify(&env, &leaf(&env, &a), &a, &Vec::new(&env)));
+        assert_eq!(root(&env, &Vec::new(&env)), None);
+
+        // Pruebas más largas que MAX_PROOF_DEPTH se rechazan sin calcular
+        let b = Address::generate(&env);
+        let pair = soroban_sdk::vec![&env, a.clone(), b];
+        let root = root(&env, &pair).unwrap();
+        assert!(verify(&env, &root, &a, &proof(&env, &pair, 0).unwrap()));
+        let mut too_deep: Vec<BytesN<32>> = Vec::new(&env);
+        for _ in 0..=MAX_PROOF_DEPTH {
+            too_deep.push_back(root.clone());
+        }
+        assert!(!verify(&env, &root, &a, &too_deep));
+    }
+}
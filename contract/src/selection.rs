@@ -0,0 +1,260 @@
+// ═══════════════════════════════════════════════════════════════════════════
+//  Selección uniforme (y ponderada) de ganadores
+//
+//  `secret % n` sobre un u64 favorece ligeramente a los índices bajos
+//  cuando 2^64 no es múltiplo de n. Aquí se usa muestreo por rechazo
+//  sobre un flujo de palabras de 64 bits derivado del secreto revelado:
+//
+//    word(j) = primeros 8 bytes (big-endian) de
+//              SHA-256( secret_be8 || salt32 || close_entropy32 || j_be4 )
+//
+//    Para un rango n, se acepta word(j) si word(j) < 2^64 - (2^64 mod n)
+//    y el índice es word(j) % n; si no, se pasa a j + 1.
+//
+//  El contador j es global a toda la extracción: el ganador k consume
+//  palabras justo después de las usadas por el ganador k - 1.
+//
+//  `close_entropy` se fija en `close_registrations`; sin ella el streamer
+//  podría predecir el ganador y añadir cuentas propias hasta acertar.
+//
+//  Con pesos, el índice uniforme se toma sobre los boletos (peso total)
+//  y se mapea a su dueño; ver `derive_weighted_winners`.
+//
+//  Implementación de referencia equivalente: `deriveWinners` en app.js
+//  y `uniform_index` en circuits/src/main.nr (primer ganador).
+// ═══════════════════════════════════════════════════════════════════════════
+
+use soroban_sdk::{Bytes, BytesN, Env, Vec};
+
+/// Palabra `counter` del flujo pseudoaleatorio del sorteo
+pub(crate) fn draw_word(
+    env: &Env,
+    secret_number: u64,
+    salt: &BytesN<32>,
+    close_entropy: &BytesN<32>,
+    counter: u32,
+) -> u64 {
+    let mut preimage = Bytes::from_array(env, &secret_number.to_be_bytes());
+    preimage.extend_from_array(&salt.to_array());
+    preimage.extend_from_array(&close_entropy.to_array());
+    preimage.extend_from_array(&counter.to_be_bytes());
+    let digest = env.crypto().sha256(&preimage).to_array();
+    let mut word = [0u8; 8];
+    word.copy_from_slice(&digest[..8]);
+    u64::from_be_bytes(word)
+}
+
+/// Reduce `word` a `[0, n)` sin sesgo, o `None` si debe rechazarse.
+///
+/// Los valores aceptados son `[0, 2^64 - (2^64 mod n))`, cuyo tamaño es
+/// múltiplo exacto de `n`.
+pub(crate) fn reduce_unbiased(word: u64, n: u64) -> Option<u64> {
+    // 2^64 mod n calculado sin desbordar
+    let rem = (u64::MAX % n + 1) % n;
+    if word > u64::MAX - rem {
+        None
+    } else {
+        Some(word % n)
+    }
+}
+
+/// Deriva `num_winners` índices distintos en `[0, num_participants)`.
+///
+/// Extracción sin reemplazo: en la ronda `k` se obtiene un índice
+/// uniforme `r` en `[0, num_participants - k)` y se mapea al r-ésimo
+/// índice aún no elegido.
+pub(crate) fn derive_winners(
+    env: &Env,
+    secret_number: u64,
+    salt: &BytesN<32>,
+    close_entropy: &BytesN<32>,
+    num_participants: u32,
+    num_winners: u32,
+) -> Vec<u32> {
+    let mut winners: Vec<u32> = Vec::new(env);
+    // Índices ya elegidos, ordenados ascendentemente
+    let mut taken: Vec<u32> = Vec::new(env);
+    let mut counter: u32 = 0;
+
+    for k in 0..num_winners {
+        let remaining = (num_participants - k) as u64;
+        let mut idx = loop {
+            let word = draw_word(env, secret_number, salt, close_entropy, counter);
+            counter += 1;
+            if let Some(r) = reduce_unbiased(word, remaining) {
+                break r as u32;
+            }
+        };
+
+        // Saltar los índices ya elegidos (recorrido ascendente)
+        let mut pos = 0;
+        while pos < taken.len() && taken.get(pos).unwrap() <= idx {
+            idx += 1;
+            pos += 1;
+        }
+        taken.insert(pos, idx);
+        winners.push_back(idx);
+    }
+
+    winners
+}
+
+/// Deriva `num_winners` índices distintos con probabilidad proporcional
+/// a su peso.
+///
+/// Cada participante ocupa tantos boletos consecutivos como su peso. En
+/// la ronda `k` se obtiene un boleto uniforme `r` entre los que aún no
+/// pertenecen a un ganador y `locate(r, taken)` devuelve el índice dueño
+/// de ese boleto y su peso; `taken` son los pares (índice, peso) ya
+/// elegidos, cuyos boletos no cuentan. Con pesos 1 coincide con
+/// `derive_winners`.
+pub(crate) fn derive_weighted_winners<F>(
+    env: &Env,
+    secret_number: u64,
+    salt: &BytesN<32>,
+    close_entropy: &BytesN<32>,
+    total_weight: u64,
+    num_winners: u32,
+    mut locate: F,
+) -> Vec<u32>
+where
+    F: FnMut(u64, &Vec<(u32, u32)>) -> (u32, u32),
+{
+    let mut winners: Vec<u32> = Vec::new(env);
+    let mut taken: Vec<(u32, u32)> = Vec::new(env);
+    let mut remaining = total_weight;
+    let mut counter: u32 = 0;
+
+    for _ in 0..num_winners {
+        let ticket = loop {
+            let word = draw_word(env, secret_number, salt, close_entropy, counter);
+            counter += 1;
+            if let Some(r) = reduce_unbiased(word, remaining) {
+                break r;
+            }
+        };
+
+        let (idx, weight) = locate(ticket, &taken);
+        remaining -= weight as u64;
+        taken.push_back((idx, weight));
+        winners.push_back(idx);
+    }
+
+    winners
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rechazo_en_la_zona_sesgada() {
+        // 2^64 mod 3 = 1 → sólo u64::MAX cae fuera de la zona uniforme
+        assert_eq!(reduce_unbiased(u64::MAX, 3), None);
+        assert_eq!(reduce_unbiased(u64::MAX - 1, 3), Some((u64::MAX - 1) % 3));
+        // Potencias de 2 nunca rechazan
+        assert_eq!(reduce_unbiased(u64::MAX, 4), Some(3));
+        // 2^64 mod 10 = 6 → se rechazan los 6 valores superiores
+        assert_eq!(reduce_unbiased(u64::MAX - 5, 10), None);
+        assert_eq!(reduce_unbiased(u64::MAX - 6, 10), Some(9));
+    }
+
+    #[test]
+    fn test_zona_aceptada_es_multiplo_de_n() {
+        for n in [2u64, 3, 5, 7, 10, 1000, 1023, 1 << 20, (1 << 32) - 1] {
+            let rem = (u64::MAX % n + 1) % n;
+            // |[0, 2^64 - rem)| = 2^64 - rem ≡ 0 (mod n)
+            let accepted_minus_one = u64::MAX - rem;
+            assert_eq!(accepted_minus_one % n, n - 1);
+            assert_eq!(reduce_unbiased(accepted_minus_one, n), Some(n - 1));
+        }
+    }
+
+    #[test]
+    fn test_sesgo_acotado() {
+        // 6000 extracciones sobre 6 índices: cada uno debe quedar a menos
+        // de ~5σ de la media (σ = sqrt(6000 · 1/6 · 5/6) ≈ 28.9).
+        let env = Env::default();
+        env.cost_estimate().budget().reset_unlimited();
+        let salt = BytesN::from_array(&env, &[0x5a; 32]);
+        let entropy = BytesN::from_array(&env, &[0xe0; 32]);
+        let n: u32 = 6;
+        let draws: u32 = 6000;
+        let mut counts = [0u32; 6];
+        for secret in 1..=draws as u64 {
+            let w = derive_winners(&env, secret, &salt, &entropy, n, 1);
+            counts[w.get(0).unwrap() as usize] += 1;
+        }
+        let expected = draws / n;
+        for c in counts.iter() {
+            assert!(c.abs_diff(expected) < 145, "counts = {:?}", counts);
+        }
+    }
+
+    /// Dueño del boleto `ticket` recorriendo `weights` linealmente
+    fn locate_linear(weights: &[u32], ticket: u64, taken: &Vec<(u32, u32)>) -> (u32, u32) {
+        let mut below: u64 = 0;
+        for (i, w) in weights.iter().enumerate() {
+            if taken.iter().any(|(t, _)| t == i as u32) {
+                continue;
+            }
+            below += *w as u64;
+            if ticket < below {
+                return (i as u32, *w);
+            }
+        }
+        unreachable!()
+    }
+
+    #[test]
+    fn test_pesos_unitarios_equivalen_a_la_extraccion_uniforme() {
+        let env = Env::default();
+        let salt = BytesN::from_array(&env, &[0x1a; 32]);
+        let entropy = BytesN::from_array(&env, &[0xe0; 32]);
+        let ones = [1u32; 12];
+        for (n, k) in [(2u32, 1u32), (5, 3), (12, 12)] {
+            for secret in 1..20u64 {
+                let weighted = derive_weighted_winners(&env, secret, &salt, &entropy, n as u64, k, |r, taken| {
+                    locate_linear(&ones[..n as usize], r, taken)
+                });
+                assert_eq!(weighted, derive_winners(&env, secret, &salt, &entropy, n, k));
+            }
+        }
+    }
+
+    #[test]
+    fn test_extraccion_proporcional_al_peso() {
+        // Pesos 1:2:3 en 6000 extracciones → medias 1000, 2000, 3000
+        let env = Env::default();
+        env.cost_estimate().budget().reset_unlimited();
+        let salt = BytesN::from_array(&env, &[0x5a; 32]);
+        let entropy = BytesN::from_array(&env, &[0xe0; 32]);
+        let weights = [1u32, 2, 3];
+        let mut counts = [0u32; 3];
+        for secret in 1..=6000u64 {
+            let w = derive_weighted_winners(&env, secret, &salt, &entropy, 6, 1, |r, taken| {
+                locate_linear(&weights, r, taken)
+            });
+            counts[w.get(0).unwrap() as usize] += 1;
+        }
+        for (c, expected) in counts.iter().zip([1000u32, 2000, 3000]) {
+            assert!(c.abs_diff(expected) < 200, "counts = {:?}", counts);
+        }
+    }
+
+    #[test]
+    fn test_vectores_de_referencia() {
+        // Mismos vectores que produce `deriveWinners` en app.js
+        let env = Env::default();
+        let salt = BytesN::from_array(&env, &[0x1a; 32]);
+        let entropy = BytesN::from_array(&env, &[0xe0; 32]);
+        assert_eq!(
+            derive_winners(&env, 13, &salt, &entropy, 3, 1),
+            soroban_sdk::vec![&env, 1u32]
+        );
+        assert_eq!(
+            derive_winners(&env, 0x5eed_1234_abcd, &salt, &entropy, 5, 3),
+            soroban_sdk::vec![&env, 0u32, 4, 1]
+        );
+    }
+}
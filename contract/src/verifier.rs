@@ -0,0 +1,225 @@
+// ═══════════════════════════════════════════════════════════════════════════
+//  Verificador Groth16 sobre BN254
+//
+//  Verifica on-chain la prueba de circuits/src/main.nr usando las host
+//  functions BN254 de Protocol 25 (suma/multiplicación en G1 y
+//  multi-pairing). El circuito se compila a Groth16 con un backend ACIR
+//  compatible; la clave de verificación resultante se carga una vez con
+//  `set_verification_key`.
+//
+//  Ecuación de verificación (con vk_x = IC[0] + Σ input_i · IC[i+1]):
+//
+//    e(-A, B) · e(α, β) · e(vk_x, γ) · e(C, δ) == 1
+//
+//  Codificación (compatible con Ethereum / EIP-197):
+//    · G1: be(X) || be(Y)                        — 64 bytes
+//    · G2: be(X.c1) || be(X.c0) || be(Y.c1) || be(Y.c0) — 128 bytes
+//    · Entradas públicas: elementos de Fr en big-endian (32 bytes)
+//
+//  Entradas públicas del circuito, en el orden del ABI de Noir:
+//    [0]      public_commitment
+//    [1]      number_of_participants
+//    [2..34]  close_entropy (un byte por elemento)
+//    [34]     winner_index (salida pública)
+// ═══════════════════════════════════════════════════════════════════════════
+
+use soroban_sdk::{
+    contracttype,
+    crypto::bn254::{Bn254G1Affine, Bn254G2Affine, Fr},
+    BytesN, Env, Vec,
+};
+
+/// Número de entradas públicas del circuito (incluida la salida)
+pub const NUM_PUBLIC_INPUTS: u32 = 35;
+
+/// Tope de `number_of_participants` en el circuito (`MAX_PARTICIPANTS`
+/// de main.nr). Con pesos la entrada es el total de boletos, que puede
+/// superarlo: esas sesiones sólo se revelan con el secreto
+pub const MAX_CIRCUIT_TICKETS: u32 = 100_000;
+
+/// Clave de verificación Groth16 del circuito
+#[contracttype]
+#[derive(Clone)]
+pub struct VerificationKey {
+    pub alpha: BytesN<64>,
+    pub beta: BytesN<128>,
+    pub gamma: BytesN<128>,
+    pub delta: BytesN<128>,
+    /// Un punto por entrada pública más el término constante IC[0]
+    pub ic: Vec<BytesN<64>>,
+}
+
+/// Prueba Groth16 (A, B, C)
+#[contracttype]
+#[derive(Clone)]
+pub struct Groth16Proof {
+    pub a: BytesN<64>,
+    pub b: BytesN<128>,
+    pub c: BytesN<64>,
+}
+
+/// Verifica `proof` para `public_inputs` con la clave `vk`.
+///
+/// Rechaza entradas no canónicas (>= r): de lo contrario dos codificaciones
+/// distintas del mismo elemento pasarían la misma prueba.
+pub(crate) fn verify(
+    env: &Env,
+    vk: &VerificationKey,
+    proof: &Groth16Proof,
+    public_inputs: &Vec<BytesN<32>>,
+) -> bool {
+    if vk.ic.len() != public_inputs.len() + 1 {
+        return false;
+    }
+
+    let bn254 = env.crypto().bn254();
+    let mut vk_x = Bn254G1Affine::from_bytes(vk.ic.get(0).unwrap());
+    for (i, input) in public_inputs.iter().enumerate() {
+        let scalar = Fr::from_bytes(input.clone());
+        if scalar.to_bytes() != input {
+            return false;
+        }
+        let term = bn254.g1_mul(
+            &Bn254G1Affine::from_bytes(vk.ic.get(i as u32 + 1).unwrap()),
+            &scalar,
+        );
+        vk_x = bn254.g1_add(&vk_x, &term);
+    }
+
+    let neg_a = -Bn254G1Affine::from_bytes(proof.a.clone());
+    let g1: Vec<Bn254G1Affine> = soroban_sdk::vec![
+        env,
+        neg_a,
+        Bn254G1Affine::from_bytes(vk.alpha.clone()),
+        vk_x,
+        Bn254G1Affine::from_bytes(proof.c.clone()),
+    ];
+    let g2: Vec<Bn254G2Affine> = soroban_sdk::vec![
+        env,
+        Bn254G2Affine::from_bytes(proof.b.clone()),
+        Bn254G2Affine::from_bytes(vk.beta.clone()),
+        Bn254G2Affine::from_bytes(vk.gamma.clone()),
+        Bn254G2Affine::from_bytes(vk.delta.clone()),
+    ];
+    bn254.pairing_check(g1, g2)
+}
+
+/// Codifica `value` como elemento de Fr big-endian de 32 bytes
+pub(crate) fn field_from_u32(env: &Env, value: u32) -> BytesN<32> {
+    let mut bytes = [0u8; 32];
+    bytes[28..].copy_from_slice(&value.to_be_bytes());
+    BytesN::from_array(env, &bytes)
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+
+    /// Generador de G1: (1, 2)
+    pub(crate) fn g1_generator(env: &Env) -> Bn254G1Affine {
+        let mut bytes = [0u8; 64];
+        bytes[31] = 1;
+        bytes[63] = 2;
+        Bn254G1Affine::from_bytes(BytesN::from_array(env, &bytes))
+    }
+
+    /// Generador de G2 (codificación EIP-197)
+    pub(crate) fn g2_generator(env: &Env) -> BytesN<128> {
+        let hex = "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2\
+                   1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed\
+                   090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b\
+                   12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa";
+        let mut bytes = [0u8; 128];
+        for (i, b) in bytes.iter_mut().enumerate() {
+            *b = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap();
+        }
+        BytesN::from_array(env, &bytes)
+    }
+
+    fn g1_times(env: &Env, k: u32) -> Bn254G1Affine {
+        env.crypto()
+            .bn254()
+            .g1_mul(&g1_generator(env), &Fr::from_bytes(field_from_u32(env, k)))
+    }
+
+    /// Clave sintética con β = γ = δ = g2 e IC[i] = (i + 2)·g1, y una
+    /// prueba que satisface la ecuación para `inputs`:
+    /// con B = g2 basta A = α + vk_x + C (todo emparejado con g2).
+    ///
+    /// No prueba la solidez del circuito, sólo la aritmética del
+    /// verificador y la codificación de puntos y entradas.
+    pub(crate) fn synthetic_proof(
+        env: &Env,
+        inputs: &Vec<BytesN<32>>,
+    ) -> (VerificationKey, Groth16Proof) {
+        let bn254 = env.crypto().bn254();
+        let g2 = g2_generator(env);
+        let alpha = g1_times(env, 7);
+        let c = g1_times(env, 11);
+
+        let mut ic: Vec<BytesN<64>> = Vec::new(env);
+        for i in 0..=inputs.len() {
+            ic.push_back(g1_times(env, i + 2).to_bytes());
+        }
+        let mut vk_x = Bn254G1Affine::from_bytes(ic.get(0).unwrap());
+        for (i, input) in inputs.iter().enumerate() {
+            let term = bn254.g1_mul(
+                &Bn254G1Affine::from_bytes(ic.get(i as u32 + 1).unwrap()),
+                &Fr::from_bytes(input),
+            );
+            vk_x = bn254.g1_add(&vk_x, &term);
+        }
+        let a = bn254.g1_add(&bn254.g1_add(&alpha, &vk_x), &c);
+
+        let vk = VerificationKey {
+            alpha: alpha.to_bytes(),
+            beta: g2.clone(),
+            gamma: g2.clone(),
+            delta: g2.clone(),
+            ic,
+        };
+        let proof = Groth16Proof { a: a.to_bytes(), b: g2, c: c.to_bytes() };
+        (vk, proof)
+    }
+
+    #[test]
+    fn test_prueba_valida_y_entradas_alteradas() {
+        let env = Env::default();
+        env.cost_estimate().budget().reset_unlimited();
+        let inputs = soroban_sdk::vec![
+            &env,
+            field_from_u32(&env, 123),
+            field_from_u32(&env, 5),
+            field_from_u32(&env, 2),
+        ];
+        let (vk, proof) = synthetic_proof(&env, &inputs);
+        assert!(verify(&env, &vk, &proof, &inputs));
+
+        // Cambiar cualquier entrada invalida la prueba
+        let mut tampered = inputs.clone();
+        tampered.set(2, field_from_u32(&env, 3));
+        assert!(!verify(&env, &vk, &proof, &tampered));
+
+        // Número de entradas distinto al de la clave
+        tampered.pop_back();
+        assert!(!verify(&env, &vk, &proof, &tampered));
+    }
+
+    #[test]
+    fn test_entrada_no_canonica_rechazada() {
+        let env = Env::default();
+        env.cost_estimate().budget().reset_unlimited();
+        let inputs = soroban_sdk::vec![&env, field_from_u32(&env, 1)];
+        let (vk, proof) = synthetic_proof(&env, &inputs);
+        // r + 1 ≡ 1 (mod r), pero no es la codificación canónica
+        let r_plus_one = BytesN::from_array(
+            &env,
+            &[
+                0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81,
+                0x81, 0x58, 0x5d, 0x28, 0x33, 0xe8, 0x48, 0x79, 0xb9, 0x70, 0x91, 0x43, 0xe1,
+                0xf5, 0x93, 0xf0, 0x00, 0x00, 0x02,
+            ],
+        );
+        assert!(!verify(&env, &vk, &proof, &soroban_sdk::vec![&env, r_plus_one]));
+    }
+}
@@ -0,0 +1,161 @@
+// ═══════════════════════════════════════════════════════════════════════════
+//  Pesos de participantes
+//
+//  Cada participante tiene un peso (boletos) de 1 a `MAX_WEIGHT`; por
+//  defecto 1. Sólo se guarda el peso de quien lo tiene distinto de 1 y,
+//  en un árbol de Fenwick, el peso EXTRA (peso - 1) acumulado:
+//
+//    nodo p (1-based) = Σ extra de las posiciones (p - lowbit(p), p]
+//
+//  El peso efectivo de un rango de posiciones es su tamaño más su extra,
+//  así que una inscripción con peso 1 no toca el árbol y sigue costando
+//  lo mismo que sin pesos. Cambiar un peso actualiza O(log n) nodos y
+//  localizar al dueño de un boleto lee O(log n) nodos: el reveal nunca
+//  recorre la lista.
+//
+//  El árbol tiene `max_participants` posiciones (la capacidad de la
+//  sesión): un nodo por encima de `num_participants` todavía cubre
+//  posiciones que pueden inscribirse, pero ninguno pasa de la capacidad.
+//  Un cambio escribe como mucho ⌊log2(capacidad)⌋ + 1 nodos.
+//
+//  Pesos y nodos se guardan con el TTL máximo de la red: la sesión se
+//  extiende en cada inscripción y el árbol no puede recorrerse entero
+//  para acompañarla, así que se escribe de modo que la sobreviva.
+//
+//  Las claves van por `DrawSession::weight_tree`, no por el ID de sesión:
+//  cada sesión abierta recibe un árbol nuevo, así que un ID reutilizado
+//  tras caducar la sesión no hereda los pesos de la anterior.
+// ═══════════════════════════════════════════════════════════════════════════
+
+use soroban_sdk::{Env, Vec};
+
+use crate::DataKey;
+
+/// Peso máximo de un participante. Con `MAX_PARTICIPANTS` el peso total
+/// (≤ 10^9) cabe en u32
+pub(crate) const MAX_WEIGHT: u32 = 10_000;
+
+/// Peso del participante `index` (1 si nunca se cambió)
+pub(crate) fn weight_of(env: &Env, tree: u32, index: u32) -> u32 {
+    env.storage()
+        .temporary()
+        .get(&DataKey::ParticipantWeight(tree, index))
+        .unwrap_or(1)
+}
+
+/// Fija el peso del participante `index` y devuelve el anterior.
+/// El llamador valida `weight` y actualiza el peso total de la sesión.
+/// `capacity` es el `max_participants` de la sesión.
+pub(crate) fn set_weight(env: &Env, tree: u32, capacity: u32, index: u32, weight: u32) -> u32 {
+    let previous = weight_of(env, tree, index);
+    store_weight(env, tree, index, weight);
+    add_extra(env, tree, capacity, [(index, weight as i64 - previous as i64), (0, 0)]);
+    previous
+}
+
+/// Baja con swap-with-last: el peso de `last` pasa a `index` y `last`
+/// queda con peso 1. Los dos caminos se recorren en una sola pasada;
+/// desde donde se unen sólo se escribe si el saldo neto no es 0 (si el
+/// participante que se va tenía peso 1, nunca).
+pub(crate) fn move_last(env: &Env, tree: u32, capacity: u32, index: u32, last: u32) {
+    let removed = weight_of(env, tree, index);
+    let moved = weight_of(env, tree, last);
+    store_weight(env, tree, index, moved);
+    store_weight(env, tree, last, 1);
+    add_extra(
+        env,
+        tree,
+        capacity,
+        [(index, moved as i64 - removed as i64), (last, 1 - moved as i64)],
+    );
+}
+
+/// Guarda un peso (1 = sin entrada)
+fn store_weight(env: &Env, tree: u32, index: u32, weight: u32) {
+    let key = DataKey::ParticipantWeight(tree, index);
+    if weight == 1 {
+        env.storage().temporary().remove(&key);
+    } else {
+        store(env, &key, weight);
+    }
+}
+
+/// Suma cada (índice, delta) de extra al árbol. Los caminos de los dos
+/// índices avanzan juntos en orden de nodo y un nodo compartido se
+/// escribe una sola vez con la suma de ambos deltas.
+fn add_extra(env: &Env, tree: u32, capacity: u32, updates: [(u32, i64); 2]) {
+    // (nodo actual, delta); u32::MAX = camino terminado
+    let mut paths = updates.map(|(index, delta)| if delta == 0 { (u32::MAX, 0) } else { (index + 1, delta) });
+    loop {
+        let node = paths[0].0.min(paths[1].0);
+        if node > capacity {
+            break;
+        }
+        let mut delta = 0;
+        for path in paths.iter_mut() {
+            if path.0 == node {
+                delta += path.1;
+                path.0 += node & node.wrapping_neg();
+            }
+        }
+        if delta != 0 {
+            let key = DataKey::WeightNode(tree, node);
+            let extra: u32 = env.storage().temporary().get(&key).unwrap_or(0);
+            let extra = (extra as i64 + delta) as u32;
+            if extra == 0 {
+                env.storage().temporary().remove(&key);
+            } else {
+                store(env, &key, extra);
+            }
+        }
+    }
+}
+
+/// Escribe una entrada del árbol con el TTL máximo (ver cabecera)
+fn store(env: &Env, key: &DataKey, value: u32) {
+    let max_ttl = env.storage().max_ttl();
+    env.storage().temporary().set(key, &value);
+    env.storage().temporary().extend_ttl(key, max_ttl, max_ttl);
+}
+
+/// Dueño del boleto `ticket` entre los `num_participants` primeros
+/// índices, sin contar los boletos de `taken` (pares índice, peso).
+/// Devuelve (índice, peso). Requiere `ticket` < peso total restante.
+///
+/// Descenso binario por el árbol: en cada nivel se salta el nodo entero
+/// si todos sus boletos quedan por debajo de `ticket`.
+pub(crate) fn locate(
+    env: &Env,
+    tree: u32,
+    num_participants: u32,
+    ticket: u64,
+    taken: &Vec<(u32, u32)>,
+) -> (u32, u32) {
+    // Posiciones [1, pos] ya descartadas y sus boletos efectivos
+    let mut pos: u32 = 0;
+    let mut below: u64 = 0;
+    let mut step: u32 = 1 << (31 - num_participants.leading_zeros());
+    while step > 0 {
+        let next = pos + step;
+        if next <= num_participants {
+            let extra: u32 = env
+                .storage()
+                .temporary()
+                .get(&DataKey::WeightNode(tree, next))
+                .unwrap_or(0);
+            // El nodo `next` cubre los índices [pos, next)
+            let mut span = step as u64 + extra as u64;
+            for (index, weight) in taken.iter() {
+                if index >= pos && index < next {
+                    span -= weight as u64;
+                }
+            }
+            if below + span <= ticket {
+                pos = next;
+                below += span;
+            }
+        }
+        step >>= 1;
+    }
+    (pos, weight_of(env, tree, pos))
+}
@@ -87,13 +87,14 @@ echo "  Contract ID: $CONTRACT_ID"
 
 # ── 6. Inicializar contrato ──────────────────────────────────────────────────
 echo ""
-echo "[6/6] Inicializando contrato con Game Hub..."
+echo "[6/6] Inicializando contrato (admin: $DEPLOYER_ADDR) con Game Hub..."
 stellar contract invoke \
     --id "$CONTRACT_ID" \
     --source "$ACCOUNT" \
     --network "$NETWORK" \
     -- \
     initialize \
+    --admin "$DEPLOYER_ADDR" \
     --game_hub "$GAME_HUB"
 echo "  Contrato inicializado correctamente."
 
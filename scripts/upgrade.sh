@@ -0,0 +1,89 @@
+#!/usr/bin/env bash
+# ═══════════════════════════════════════════════════════════════════════════
+#  upgrade.sh — Reemplaza el WASM de un ZK Roulette ya desplegado
+#
+#  Conserva el CONTRACT_ID y el storage: los frontends no cambian.
+#  La cuenta debe ser el admin del contrato (ver `initialize`).
+#
+#  USO:
+#    ./scripts/upgrade.sh CONTRACT_ID [NOMBRE_CUENTA]   # por defecto: "deployer"
+# ═══════════════════════════════════════════════════════════════════════════
+
+set -euo pipefail
+
+if [ $# -lt 1 ]; then
+    echo "USO: $0 CONTRACT_ID [NOMBRE_CUENTA]"
+    exit 1
+fi
+
+CONTRACT_ID="$1"
+ACCOUNT="${2:-deployer}"
+NETWORK="testnet"
+
+echo ""
+echo "  Contrato: $CONTRACT_ID"
+echo "  Cuenta  : $ACCOUNT"
+echo "  Red     : $NETWORK"
+echo ""
+
+# ── 1. Compilar contrato ─────────────────────────────────────────────────────
+echo "[1/4] Compilando contrato Soroban..."
+stellar contract build --manifest-path contract/Cargo.toml
+WASM_PATH="contract/target/wasm32-unknown-unknown/release/zk_roulette.wasm"
+if [ ! -f "$WASM_PATH" ]; then
+    WASM_PATH=$(find contract/target -name "*.wasm" | head -1)
+    if [ -z "$WASM_PATH" ]; then
+        echo "ERROR: No se encontró el WASM compilado"
+        exit 1
+    fi
+fi
+
+# ── 2. Subir WASM a la red ───────────────────────────────────────────────────
+echo ""
+echo "[2/4] Subiendo WASM a Testnet..."
+WASM_HASH=$(stellar contract install \
+    --wasm "$WASM_PATH" \
+    --source "$ACCOUNT" \
+    --network "$NETWORK")
+echo "  WASM Hash: $WASM_HASH"
+
+# ── 3. Upgrade ───────────────────────────────────────────────────────────────
+echo ""
+echo "[3/4] Reemplazando el WASM del contrato..."
+stellar contract invoke \
+    --id "$CONTRACT_ID" \
+    --source "$ACCOUNT" \
+    --network "$NETWORK" \
+    -- \
+    upgrade \
+    --new_wasm_hash "$WASM_HASH"
+
+# ── 4. Migrar storage ────────────────────────────────────────────────────────
+# AlreadyMigrated (#7) indica que el nuevo WASM no cambia el layout.
+# Cualquier otro fallo (UnknownStorageVersion #9, firma, red, ...) deja el
+# WASM nuevo activo sobre storage sin migrar: hay que abortar.
+echo ""
+echo "[4/4] Migrando storage..."
+if MIGRATE_OUTPUT=$(stellar contract invoke \
+    --id "$CONTRACT_ID" \
+    --source "$ACCOUNT" \
+    --network "$NETWORK" \
+    -- \
+    migrate 2>&1); then
+    echo "  Storage migrado."
+elif grep -q "Error(Contract, #7)" <<< "$MIGRATE_OUTPUT"; then
+    echo "  Sin migración pendiente."
+else
+    echo "$MIGRATE_OUTPUT"
+    echo "ERROR: migrate falló; el contrato ya ejecuta el WASM nuevo"
+    exit 1
+fi
+
+echo ""
+echo "  Versión de storage: $(stellar contract invoke \
+    --id "$CONTRACT_ID" \
+    --source "$ACCOUNT" \
+    --network "$NETWORK" \
+    -- \
+    get_version)"
+echo ""